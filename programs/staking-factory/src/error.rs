@@ -9,12 +9,14 @@ pub enum StakingError {
     #[msg("0 is not allowed as a value")]
     Zero,
     /// 6002 0x1772
+    /// no longer returned as the staking type may be changed, kept for the error codes
     #[msg("Cannot change staking type, may only change conditions")]
     CannotChangeStakingType,
     /// 6003 0x1773
     #[msg("Not enough tokens for operation")]
     InsufficientBalance,
     /// 6004 0x1774
+    /// no longer returned as unstakes may run concurrently, kept for the error codes
     #[msg("There is an unfinished unstake")]
     UnstakeActive,
     /// 6005 0x1775
@@ -23,4 +25,100 @@ pub enum StakingError {
     /// 6006 0x1776
     #[msg("The unstake timelock has not yet expired")]
     UnstakeTimelock,
+    /// 6007 0x1777
+    #[msg("Fee exceeds the maximum allowed")]
+    FeeTooHigh,
+    /// 6008 0x1778
+    #[msg("Signer is not the pending authority")]
+    NotPendingAuthority,
+    /// 6009 0x1779
+    #[msg("Not enough funded rewards for claim")]
+    InsufficientFunding,
+    /// 6010 0x177a
+    #[msg("Amount exceeds rewards not reserved for members")]
+    ReservedRewards,
+    /// 6011 0x177b
+    #[msg("End time must be after start time and the current end time")]
+    InvalidEndTs,
+    /// 6012 0x177c
+    #[msg("The staking has already ended")]
+    StakingEnded,
+    /// 6013 0x177d
+    #[msg("Too many unfinished unstakes")]
+    TooManyUnstakes,
+    /// 6014 0x177e
+    #[msg("Penalty must not exceed the unstaked amount")]
+    InvalidPenalty,
+    /// 6015 0x177f
+    #[msg("Instant unstake is not enabled for this staking")]
+    InstantUnstakeDisabled,
+    /// 6016 0x1780
+    #[msg("Penalty destination does not match the penalty policy")]
+    InvalidPenaltyDestination,
+    /// 6017 0x1781
+    #[msg("Max lock multiplier must be at least 1x")]
+    InvalidLockParams,
+    /// 6018 0x1782
+    #[msg("Locking is not enabled for this staking")]
+    LockDisabled,
+    /// 6019 0x1783
+    #[msg("Lock duration must be positive, within the max and not shorten the current lock")]
+    InvalidLockDuration,
+    /// 6020 0x1784
+    #[msg("Stake is locked")]
+    StakeLocked,
+    /// 6021 0x1785
+    #[msg("Too many reward streams")]
+    TooManyRewardStreams,
+    /// 6022 0x1786
+    #[msg("Reward streams are missing or invalid")]
+    InvalidRewardStreams,
+    /// 6023 0x1787
+    #[msg("Decay must decrease the amount")]
+    InvalidDecay,
+    /// 6024 0x1788
+    #[msg("Tiers must be increasing in required and reward amounts")]
+    InvalidTiers,
+    /// 6025 0x1789
+    #[msg("Stake and reward mints differ")]
+    MintsDiffer,
+    /// 6026 0x178a
+    #[msg("Member has not enabled auto compounding")]
+    AutoCompoundDisabled,
+    /// 6027 0x178b
+    #[msg("Member still has balances or pending unstakes")]
+    MemberNotEmpty,
+    /// 6028 0x178c
+    #[msg("Staking is closing")]
+    StakingClosing,
+    /// 6029 0x178d
+    #[msg("Staking is not closing")]
+    StakingNotClosing,
+    /// 6030 0x178e
+    #[msg("Staking members still have tokens or unclaimed rewards")]
+    MembersRemaining,
+    /// 6031 0x178f
+    #[msg("Instruction is paused")]
+    Paused,
+    /// 6032 0x1790
+    #[msg("Unknown pause flags")]
+    InvalidPauseFlags,
+    /// 6033 0x1791
+    #[msg("Signer is neither the staking nor the factory authority")]
+    NotAuthority,
+    /// 6034 0x1792
+    #[msg("Emergency unstake is not enabled for this staking")]
+    EmergencyUnstakeDisabled,
+    /// 6035 0x1793
+    #[msg("Config history pages are missing or invalid")]
+    MissingConfigPages,
+    /// 6036 0x1794
+    #[msg("Token program is not the token program or Token-2022 of the accounts")]
+    InvalidTokenProgram,
+    /// 6037 0x1795
+    #[msg("Token account has a wrong mint or owner")]
+    InvalidTokenAccount,
+    /// 6038 0x1796
+    #[msg("Mint has an extension that is not supported")]
+    UnsupportedMintExtension,
 }
@@ -1,20 +1,133 @@
-use crate::reward::*;
+use crate::{reward::*, state::*};
 use anchor_lang::prelude::*;
 
 #[event]
-pub struct InitializeEvent {}
+pub struct InitializeEvent {
+    pub fee: u16,
+}
+
+#[event]
+pub struct ChangeFactoryFeeEvent {
+    pub fee: u16,
+}
+
+#[event]
+pub struct ChangeStakingFeeEvent {
+    pub id: u16,
+    pub fee_override: Option<u16>,
+}
+
+#[event]
+pub struct ProposeFactoryAuthorityEvent {
+    pub authority: Pubkey,
+    pub pending_authority: Option<Pubkey>,
+}
+
+#[event]
+pub struct AcceptFactoryAuthorityEvent {
+    pub old_authority: Pubkey,
+    pub new_authority: Pubkey,
+}
 
 #[event]
 pub struct CreateStakingEvent {
     pub id: u16,
     pub authority: Pubkey,
     pub reward_params: RewardParams,
+    pub start_ts: u32,
+    pub end_ts: u32,
+    pub settings: PoolSettings,
+}
+
+#[event]
+pub struct ExtendStakingEvent {
+    pub id: u16,
+    pub end_ts: u32,
+}
+
+#[event]
+pub struct ProposeStakingAuthorityEvent {
+    pub id: u16,
+    pub authority: Pubkey,
+    pub pending_authority: Option<Pubkey>,
+}
+
+#[event]
+pub struct AcceptStakingAuthorityEvent {
+    pub id: u16,
+    pub old_authority: Pubkey,
+    pub new_authority: Pubkey,
 }
 
 #[event]
 pub struct ChangeConfigEvent {
     pub id: u16,
     pub new_reward_params: Option<RewardParams>,
+    pub new_settings: Option<PoolSettings>,
+}
+
+#[event]
+pub struct AddRewardStreamEvent {
+    pub id: u16,
+    pub index: u8,
+    pub reward_mint: Pubkey,
+    pub reward_params: RewardParams,
+    pub start_ts: u32,
+    pub end_ts: u32,
+}
+
+#[event]
+pub struct ChangeStreamConfigEvent {
+    pub id: u16,
+    pub index: u8,
+    pub new_reward_params: RewardParams,
+}
+
+#[event]
+pub struct ExtendRewardStreamEvent {
+    pub id: u16,
+    pub index: u8,
+    pub end_ts: u32,
+}
+
+#[event]
+pub struct FundRewardsEvent {
+    pub id: u16,
+    pub funder: Pubkey,
+    pub amount: u64,
+    pub total_funded: u64,
+    pub total_claimed: u64,
+    pub rewards_liability: u64,
+}
+
+#[event]
+pub struct FundRewardStreamEvent {
+    pub id: u16,
+    pub index: u8,
+    pub funder: Pubkey,
+    pub amount: u64,
+    pub total_funded: u64,
+    pub total_claimed: u64,
+    pub rewards_liability: u64,
+}
+
+#[event]
+pub struct WithdrawExcessRewardsEvent {
+    pub id: u16,
+    pub amount: u64,
+    pub total_funded: u64,
+    pub total_claimed: u64,
+    pub rewards_liability: u64,
+}
+
+#[event]
+pub struct WithdrawExcessStreamRewardsEvent {
+    pub id: u16,
+    pub index: u8,
+    pub amount: u64,
+    pub total_funded: u64,
+    pub total_claimed: u64,
+    pub rewards_liability: u64,
 }
 
 #[event]
@@ -39,17 +152,70 @@ pub struct ClaimRewardEvent {
     pub beneficiary: Pubkey,
     pub amount_to_beneficiary: u64,
     pub factory_fee: u64,
+    pub fee: u16,
+}
+
+#[event]
+pub struct ClaimStreamRewardEvent {
+    pub beneficiary: Pubkey,
+    pub index: u8,
+    pub amount_to_beneficiary: u64,
+    pub factory_fee: u64,
+    pub fee: u16,
+}
+
+#[event]
+pub struct CompoundEvent {
+    pub beneficiary: Pubkey,
+    pub amount: u64,
+    pub factory_fee: u64,
+    pub fee: u16,
+}
+
+#[event]
+pub struct SetAutoCompoundEvent {
+    pub beneficiary: Pubkey,
+    pub auto_compound: bool,
 }
 
 #[event]
 pub struct StartUnstakeEvent {
     pub beneficiary: Pubkey,
     pub amount: u64,
+    pub end_ts: u32,
+}
+
+#[event]
+pub struct CancelUnstakeEvent {
+    pub beneficiary: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct InstantUnstakeEvent {
+    pub beneficiary: Pubkey,
+    pub amount: u64,
+    pub penalty: u64,
+    pub policy: PenaltyPolicy,
+}
+
+#[event]
+pub struct ClaimPenaltyShareEvent {
+    pub beneficiary: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct LockEvent {
+    pub beneficiary: Pubkey,
+    pub end_ts: u32,
+    pub multiplier: u32,
 }
 
 #[event]
 pub struct EndUnstakeEvent {
     pub beneficiary: Pubkey,
+    pub amount: u64,
 }
 
 #[event]
@@ -57,3 +223,48 @@ pub struct WithdrawEvent {
     pub beneficiary: Pubkey,
     pub amount: u64,
 }
+
+#[event]
+pub struct CloseMemberEvent {
+    pub beneficiary: Pubkey,
+}
+
+#[event]
+pub struct SunsetStakingEvent {
+    pub id: u16,
+    pub end_ts: u32,
+}
+
+#[event]
+pub struct CloseStakingEvent {
+    pub id: u16,
+}
+
+#[event]
+pub struct SetFactoryPausedEvent {
+    pub paused: u8,
+}
+
+#[event]
+pub struct SetStakingPausedEvent {
+    pub id: u16,
+    pub paused: u8,
+}
+
+#[event]
+pub struct SetEmergencyUnstakeEvent {
+    pub id: u16,
+    pub enabled: bool,
+}
+
+#[event]
+pub struct EmergencyUnstakeEvent {
+    pub beneficiary: Pubkey,
+    pub amount: u64,
+    pub forfeited_rewards: u64,
+}
+
+#[event]
+pub struct ExpireLockEvent {
+    pub beneficiary: Pubkey,
+}
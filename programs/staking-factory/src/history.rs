@@ -0,0 +1,317 @@
+use crate::{error::*, reward::*, state::*};
+use anchor_lang::{prelude::*, system_program, AccountsClose, Discriminator};
+
+fn is_account<T: Discriminator>(account: &AccountInfo) -> bool {
+    account.owner == &crate::ID
+        && account
+            .try_borrow_data()
+            .map(|data| data.starts_with(&T::discriminator()))
+            .unwrap_or(false)
+}
+
+/// Loads configs from the `ConfigHistoryPage`s leading `accounts`, which must be
+/// consecutive up to the last page, and returns the accounts after them
+pub fn load_configs<'a, 'info>(
+    config_history: &Account<ConfigHistory>,
+    accounts: &'a [AccountInfo<'info>],
+) -> Result<(Configs, &'a [AccountInfo<'info>])> {
+    let pages_count = accounts
+        .iter()
+        .take_while(|account| is_account::<ConfigHistoryPage>(account))
+        .count();
+    if pages_count == 0 || pages_count > config_history.pages_count as usize {
+        return err!(StakingError::MissingConfigPages);
+    }
+    let (pages, accounts) = accounts.split_at(pages_count);
+    let first_index = config_history.pages_count - pages_count as u32;
+
+    let mut configs = Configs {
+        start_ts: config_history.start_ts,
+        reward_params: Vec::new(),
+        start_timestamps: Vec::new(),
+        end_ts: config_history.end_ts,
+        from_first_page: first_index == 0,
+    };
+    for (index, page) in (first_index..).zip(pages) {
+        let page = Account::<ConfigHistoryPage>::try_from(page)?;
+        let page_key = Pubkey::create_program_address(
+            &[
+                b"config_history_page",
+                config_history.key().as_ref(),
+                &index.to_le_bytes(),
+                &[page.bump],
+            ],
+            &crate::ID,
+        )
+        .map_err(|_| StakingError::MissingConfigPages)?;
+        if page.key() != page_key {
+            return err!(StakingError::MissingConfigPages);
+        }
+
+        configs.reward_params.extend_from_slice(&page.reward_params);
+        configs
+            .start_timestamps
+            .extend_from_slice(&page.start_timestamps);
+    }
+
+    Ok((configs, accounts))
+}
+
+/// Splits the `T` pages leading `accounts` off, there must be `count` of them
+fn split_pages<'a, 'info, T: Discriminator>(
+    accounts: &'a [AccountInfo<'info>],
+    count: u32,
+) -> Result<(&'a [AccountInfo<'info>], &'a [AccountInfo<'info>])> {
+    let count = count as usize;
+    if accounts.len() < count || !accounts[..count].iter().all(is_account::<T>) {
+        return err!(StakingError::MissingConfigPages);
+    }
+
+    Ok(accounts.split_at(count))
+}
+
+/// Closes all `count` pages of `seed` leading `accounts`, returns the accounts after them
+fn close_pages<'a, 'info, T>(
+    config_history: &Account<'info, ConfigHistory>,
+    seed: &[u8],
+    bump: fn(&T) -> u8,
+    count: u32,
+    accounts: &'a [AccountInfo<'info>],
+    sol_destination: &AccountInfo<'info>,
+) -> Result<&'a [AccountInfo<'info>]>
+where
+    T: AccountSerialize + AccountDeserialize + Owner + Clone + Discriminator,
+{
+    let (pages, accounts) = split_pages::<T>(accounts, count)?;
+    for (index, page) in (0u32..).zip(pages) {
+        let page = Account::<T>::try_from(page)?;
+        let page_key = Pubkey::create_program_address(
+            &[
+                seed,
+                config_history.key().as_ref(),
+                &index.to_le_bytes(),
+                &[bump(&page)],
+            ],
+            &crate::ID,
+        )
+        .map_err(|_| StakingError::MissingConfigPages)?;
+        if page.key() != page_key {
+            return err!(StakingError::MissingConfigPages);
+        }
+        page.close(sol_destination.clone())?;
+    }
+
+    Ok(accounts)
+}
+
+/// Closes all config and settings pages of the history leading `accounts`,
+/// returns the accounts after them
+pub fn close_history_pages<'a, 'info>(
+    config_history: &Account<'info, ConfigHistory>,
+    accounts: &'a [AccountInfo<'info>],
+    sol_destination: &AccountInfo<'info>,
+) -> Result<&'a [AccountInfo<'info>]> {
+    let accounts = close_pages::<ConfigHistoryPage>(
+        config_history,
+        b"config_history_page",
+        |page| page.bump,
+        config_history.pages_count,
+        accounts,
+        sol_destination,
+    )?;
+    close_pages::<SettingsHistoryPage>(
+        config_history,
+        b"settings_history_page",
+        |page| page.bump,
+        config_history.settings_pages_count,
+        accounts,
+        sol_destination,
+    )
+}
+
+/// Creates a page at its PDA, also if someone has already sent lamports to it
+fn create_page<'info, T: AccountSerialize>(
+    page: &T,
+    space: usize,
+    account: &AccountInfo<'info>,
+    seeds: &[&[u8]],
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    let signer = &[seeds];
+    let space = 8 + space;
+    let rent = Rent::get()?.minimum_balance(space);
+
+    if account.lamports() == 0 {
+        let cpi_ctx = CpiContext::new_with_signer(
+            system_program.clone(),
+            system_program::CreateAccount {
+                from: payer.clone(),
+                to: account.clone(),
+            },
+            signer,
+        );
+        system_program::create_account(cpi_ctx, rent, space as u64, &crate::ID)?;
+    } else {
+        let lamports = rent.saturating_sub(account.lamports());
+        if lamports != 0 {
+            let cpi_ctx = CpiContext::new(
+                system_program.clone(),
+                system_program::Transfer {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+            );
+            system_program::transfer(cpi_ctx, lamports)?;
+        }
+        let cpi_ctx = CpiContext::new_with_signer(
+            system_program.clone(),
+            system_program::Allocate {
+                account_to_allocate: account.clone(),
+            },
+            signer,
+        );
+        system_program::allocate(cpi_ctx, space as u64)?;
+        let cpi_ctx = CpiContext::new_with_signer(
+            system_program.clone(),
+            system_program::Assign {
+                account_to_assign: account.clone(),
+            },
+            signer,
+        );
+        system_program::assign(cpi_ctx, &crate::ID)?;
+    }
+
+    let mut data = account.try_borrow_mut_data()?;
+    let mut writer: &mut [u8] = &mut data;
+    page.try_serialize(&mut writer)
+}
+
+/// Changes the reward params of a staking or a reward stream. The new config starts
+/// once the reward period in progress is finished if any, or replaces the last
+/// config if it is not yet started. `next_page` is created if `last_page` is full
+pub fn change_reward_params<'info>(
+    config_history: &mut Account<'info, ConfigHistory>,
+    last_page: &mut Account<'info, ConfigHistoryPage>,
+    next_page: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    reward_params: RewardParams,
+    current_ts: u32,
+) -> Result<()> {
+    let len = last_page.reward_params.len();
+    let last_start_ts = last_page.start_timestamps[len - 1];
+
+    if current_ts < last_start_ts {
+        last_page.reward_params[len - 1] = reward_params;
+        return Ok(());
+    }
+
+    // the new config may be of another type
+    let start_ts = match last_page.reward_params[len - 1].reward_period() {
+        Some(reward_period) => {
+            let time_from_last_reward = (current_ts - last_start_ts) % reward_period;
+            if time_from_last_reward == 0 {
+                current_ts
+            } else {
+                current_ts + reward_period - time_from_last_reward
+            }
+        }
+        None => current_ts,
+    };
+
+    if len < ConfigHistoryPage::MAX_LEN {
+        last_page.reward_params.push(reward_params);
+        last_page.start_timestamps.push(start_ts);
+        return Ok(());
+    }
+
+    let config_history_key = config_history.key();
+    let index = config_history.pages_count.to_le_bytes();
+    let (page_key, bump) = Pubkey::find_program_address(
+        &[b"config_history_page", config_history_key.as_ref(), &index],
+        &crate::ID,
+    );
+    if next_page.key() != page_key {
+        return err!(StakingError::MissingConfigPages);
+    }
+    create_page(
+        &ConfigHistoryPage {
+            bump,
+            reward_params: vec![reward_params],
+            start_timestamps: vec![start_ts],
+        },
+        ConfigHistoryPage::LEN,
+        next_page,
+        &[
+            b"config_history_page",
+            config_history_key.as_ref(),
+            &index,
+            &[bump],
+        ],
+        payer,
+        system_program,
+    )?;
+    config_history.pages_count += 1;
+
+    Ok(())
+}
+
+/// Records a settings change, `next_page` is created if `last_page` is full
+pub fn push_settings<'info>(
+    config_history: &mut Account<'info, ConfigHistory>,
+    last_page: &mut Account<'info, SettingsHistoryPage>,
+    next_page: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    settings: PoolSettings,
+    current_ts: u32,
+) -> Result<()> {
+    let len = last_page.settings.len();
+
+    if last_page.timestamps[len - 1] == current_ts {
+        // already changed in this second
+        last_page.settings[len - 1] = settings;
+        return Ok(());
+    }
+
+    if len < SettingsHistoryPage::MAX_LEN {
+        last_page.settings.push(settings);
+        last_page.timestamps.push(current_ts);
+        return Ok(());
+    }
+
+    let config_history_key = config_history.key();
+    let index = config_history.settings_pages_count.to_le_bytes();
+    let (page_key, bump) = Pubkey::find_program_address(
+        &[
+            b"settings_history_page",
+            config_history_key.as_ref(),
+            &index,
+        ],
+        &crate::ID,
+    );
+    if next_page.key() != page_key {
+        return err!(StakingError::MissingConfigPages);
+    }
+    create_page(
+        &SettingsHistoryPage {
+            bump,
+            settings: vec![settings],
+            timestamps: vec![current_ts],
+        },
+        SettingsHistoryPage::LEN,
+        next_page,
+        &[
+            b"settings_history_page",
+            config_history_key.as_ref(),
+            &index,
+            &[bump],
+        ],
+        payer,
+        system_program,
+    )?;
+    config_history.settings_pages_count += 1;
+
+    Ok(())
+}
@@ -0,0 +1,28 @@
+use crate::{error::*, event::*, state::*};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct AcceptFactoryAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"factory"],
+        bump = factory.bump,
+        constraint = factory.pending_authority == Some(new_authority.key()) @ StakingError::NotPendingAuthority,
+    )]
+    pub factory: Account<'info, Factory>,
+    pub new_authority: Signer<'info>,
+}
+
+pub fn accept_factory_authority(ctx: Context<AcceptFactoryAuthority>) -> Result<()> {
+    let old_authority = ctx.accounts.factory.authority;
+
+    ctx.accounts.factory.authority = ctx.accounts.new_authority.key();
+    ctx.accounts.factory.pending_authority = None;
+
+    emit!(AcceptFactoryAuthorityEvent {
+        old_authority,
+        new_authority: ctx.accounts.new_authority.key(),
+    });
+
+    Ok(())
+}
@@ -0,0 +1,27 @@
+use crate::{error::*, event::*, state::*};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct AcceptStakingAuthority<'info> {
+    #[account(
+        mut,
+        constraint = staking.pending_authority == Some(new_authority.key()) @ StakingError::NotPendingAuthority,
+    )]
+    pub staking: Account<'info, Staking>,
+    pub new_authority: Signer<'info>,
+}
+
+pub fn accept_staking_authority(ctx: Context<AcceptStakingAuthority>) -> Result<()> {
+    let old_authority = ctx.accounts.staking.authority;
+
+    ctx.accounts.staking.authority = ctx.accounts.new_authority.key();
+    ctx.accounts.staking.pending_authority = None;
+
+    emit!(AcceptStakingAuthorityEvent {
+        id: ctx.accounts.staking.id,
+        old_authority,
+        new_authority: ctx.accounts.new_authority.key(),
+    });
+
+    Ok(())
+}
@@ -0,0 +1,108 @@
+use crate::{error::*, event::*, reward::RewardParams, state::*};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount},
+};
+
+#[derive(Accounts)]
+pub struct AddRewardStream<'info> {
+    #[account(mut, has_one = authority)]
+    pub staking: Account<'info, Staking>,
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"reward_stream", staking.key().as_ref(), staking.reward_streams_count.to_le_bytes().as_ref()],
+        bump,
+        space = 8 + RewardStream::LEN,
+    )]
+    pub reward_stream: Account<'info, RewardStream>,
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"config_history", reward_stream.key().as_ref()],
+        bump,
+        space = 8 + ConfigHistory::LEN,
+    )]
+    pub config_history: Box<Account<'info, ConfigHistory>>,
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"config_history_page", config_history.key().as_ref(), &0u32.to_le_bytes()],
+        bump,
+        space = 8 + ConfigHistoryPage::LEN,
+    )]
+    pub config_history_page: Box<Account<'info, ConfigHistoryPage>>,
+    // a vault shared with the staking would mix their funds
+    #[account(
+        constraint = reward_mint.key() != staking.reward_mint @ StakingError::InvalidRewardStreams,
+        constraint = reward_mint.key() != staking.stake_mint @ StakingError::InvalidRewardStreams,
+    )]
+    pub reward_mint: Account<'info, Mint>,
+    // may be created beforehand, same as the staking vaults in `create_staking`
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::authority = staking,
+        associated_token::mint = reward_mint,
+    )]
+    pub stream_vault: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn add_reward_stream(
+    ctx: Context<AddRewardStream>,
+    reward_params: RewardParams,
+    start_ts: Option<u32>,
+    end_ts: Option<u32>,
+) -> Result<()> {
+    let ts = Clock::get()?.unix_timestamp as u32;
+
+    if ctx.accounts.staking.reward_streams_count as usize == Staking::MAX_REWARD_STREAMS {
+        return err!(StakingError::TooManyRewardStreams);
+    }
+
+    reward_params.validate_fields()?;
+
+    let start_ts = start_ts.map_or(ts, |start_ts| start_ts.max(ts));
+    let end_ts = end_ts.unwrap_or(u32::MAX);
+    if end_ts <= start_ts {
+        return err!(StakingError::InvalidEndTs);
+    }
+
+    let index = ctx.accounts.staking.reward_streams_count;
+    let reward_mint = ctx.accounts.reward_mint.key();
+
+    ctx.accounts.reward_stream.bump = *ctx.bumps.get("reward_stream").unwrap();
+    ctx.accounts.reward_stream.index = index;
+    ctx.accounts.reward_stream.reward_mint = reward_mint;
+    ctx.accounts.reward_stream.reward_params = reward_params;
+    ctx.accounts.reward_stream.reward_per_share_ts = start_ts;
+
+    ctx.accounts.config_history.bump = *ctx.bumps.get("config_history").unwrap();
+    ctx.accounts.config_history.start_ts = start_ts;
+    ctx.accounts.config_history.end_ts = end_ts;
+    ctx.accounts.config_history.pages_count = 1;
+
+    ctx.accounts.config_history_page.bump = *ctx.bumps.get("config_history_page").unwrap();
+    ctx.accounts.config_history_page.reward_params = vec![reward_params];
+    ctx.accounts.config_history_page.start_timestamps = vec![start_ts];
+
+    ctx.accounts.staking.reward_streams_count += 1;
+
+    emit!(AddRewardStreamEvent {
+        id: ctx.accounts.staking.id,
+        index,
+        reward_mint,
+        reward_params,
+        start_ts,
+        end_ts,
+    });
+
+    Ok(())
+}
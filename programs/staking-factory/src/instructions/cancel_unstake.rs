@@ -0,0 +1,54 @@
+use crate::{error::*, event::*, reward::*, state::*};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct CancelUnstake<'info> {
+    #[account(seeds = [b"factory"], bump = factory.bump)]
+    pub factory: Account<'info, Factory>,
+    #[account(
+        mut,
+        constraint = !staking.closing @ StakingError::StakingClosing,
+        constraint = !staking.is_paused(&factory, PauseFlags::STAKE) @ StakingError::Paused,
+    )]
+    pub staking: Account<'info, Staking>,
+    #[account(seeds = [b"config_history", staking.key().as_ref()], bump = config_history.bump)]
+    pub config_history: Box<Account<'info, ConfigHistory>>,
+    pub beneficiary: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"member", staking.id.to_le_bytes().as_ref(), beneficiary.key().as_ref()],
+        bump = member.bump,
+        constraint = member.pending_amount != 0 @ StakingError::UnstakeInactive,
+    )]
+    pub member: Account<'info, Member>,
+}
+
+pub fn cancel_unstake(ctx: Context<CancelUnstake>) -> Result<()> {
+    let ts = Clock::get()?.unix_timestamp as u32;
+
+    settle_rewards(
+        ts,
+        &mut ctx.accounts.staking,
+        &ctx.accounts.config_history,
+        &mut ctx.accounts.member,
+        ctx.remaining_accounts,
+    )?;
+
+    let amount = ctx.accounts.member.pending_amount;
+
+    ctx.accounts
+        .staking
+        .add_member_stake(&mut ctx.accounts.member, amount, ts)?;
+    ctx.accounts
+        .staking
+        .set_member_pending(&mut ctx.accounts.member, 0)?;
+
+    ctx.accounts.member.pending_unstakes = Default::default();
+
+    emit!(CancelUnstakeEvent {
+        beneficiary: ctx.accounts.beneficiary.key(),
+        amount,
+    });
+
+    Ok(())
+}
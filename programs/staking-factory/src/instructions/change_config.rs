@@ -1,14 +1,39 @@
-use crate::{error::*, event::*, reward::*, state::*};
+use crate::{error::*, event::*, history::*, reward::*, state::*};
 use anchor_lang::prelude::*;
 
 #[derive(Accounts)]
 pub struct ChangeConfig<'info> {
-    #[account(mut, has_one = authority)]
+    #[account(mut, has_one = authority, constraint = !staking.closing @ StakingError::StakingClosing)]
     pub staking: Account<'info, Staking>,
     #[account(mut, seeds = [b"config_history", staking.key().as_ref()], bump = config_history.bump)]
     pub config_history: Box<Account<'info, ConfigHistory>>,
-    #[account(mut, seeds = [b"stakes_history", staking.key().as_ref()], bump = stakes_history.bump)]
-    pub stakes_history: Box<Account<'info, StakesHistory>>,
+    #[account(
+        mut,
+        seeds = [
+            b"config_history_page",
+            config_history.key().as_ref(),
+            &(config_history.pages_count - 1).to_le_bytes(),
+        ],
+        bump = last_page.bump,
+    )]
+    pub last_page: Box<Account<'info, ConfigHistoryPage>>,
+    /// CHECK: created if the last page is full, its address is checked then
+    #[account(mut)]
+    pub next_page: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [
+            b"settings_history_page",
+            config_history.key().as_ref(),
+            &(config_history.settings_pages_count - 1).to_le_bytes(),
+        ],
+        bump = last_settings_page.bump,
+    )]
+    pub last_settings_page: Box<Account<'info, SettingsHistoryPage>>,
+    /// CHECK: created if the last settings page is full, its address is checked then
+    #[account(mut)]
+    pub next_settings_page: UncheckedAccount<'info>,
+    #[account(mut)]
     pub authority: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
@@ -16,57 +41,52 @@ pub struct ChangeConfig<'info> {
 pub fn change_config(
     ctx: Context<ChangeConfig>,
     new_reward_params: Option<RewardParams>,
+    new_settings: Option<PoolSettings>,
 ) -> Result<()> {
     let ts = Clock::get()?.unix_timestamp as u32;
 
-    if let Some(new_reward_params) = new_reward_params {
-        if std::mem::discriminant(&ctx.accounts.staking.reward_params)
-            != std::mem::discriminant(&new_reward_params)
-        {
-            return err!(StakingError::CannotChangeStakingType);
-        }
+    if ts >= ctx.accounts.config_history.end_ts {
+        return err!(StakingError::StakingEnded);
+    }
 
+    if let Some(new_reward_params) = new_reward_params {
         new_reward_params.validate_fields()?;
 
-        let len = ctx.accounts.config_history.len as usize;
-
-        if ts < ctx.accounts.config_history.start_timestamps[len - 1] {
-            // last config is not yet started, so just change its params
-            ctx.accounts.config_history.reward_params[len - 1] = new_reward_params;
-        } else {
-            let next_start_ts = match ctx.accounts.staking.reward_params {
-                RewardParams::Proportional { reward_period, .. } => {
-                    let time_from_last_reward = (ts
-                        - ctx.accounts.config_history.start_timestamps[len - 1])
-                        % reward_period;
-                    let next_start_ts = if time_from_last_reward == 0 {
-                        ts
-                    } else {
-                        ts + reward_period - time_from_last_reward
-                    };
-
-                    let total_rewards = (next_start_ts
-                        - ctx.accounts.config_history.start_timestamps[len - 1])
-                        / reward_period;
-                    ctx.accounts.stakes_history.offsets[len] =
-                        ctx.accounts.stakes_history.offsets[len - 1] + total_rewards as u8;
+        change_reward_params(
+            &mut ctx.accounts.config_history,
+            &mut ctx.accounts.last_page,
+            &ctx.accounts.next_page,
+            &ctx.accounts.authority,
+            &ctx.accounts.system_program,
+            new_reward_params,
+            ts,
+        )?;
+        ctx.accounts.staking.reward_params = new_reward_params;
+    }
 
-                    next_start_ts
-                }
-                _ => ts,
-            };
+    if let Some(new_settings) = new_settings {
+        new_settings.validate_fields()?;
 
-            ctx.accounts.staking.reward_params = new_reward_params;
+        push_settings(
+            &mut ctx.accounts.config_history,
+            &mut ctx.accounts.last_settings_page,
+            &ctx.accounts.next_settings_page,
+            &ctx.accounts.authority,
+            &ctx.accounts.system_program,
+            new_settings,
+            ts,
+        )?;
 
-            ctx.accounts.config_history.reward_params[len] = new_reward_params;
-            ctx.accounts.config_history.start_timestamps[len] = next_start_ts;
-            ctx.accounts.config_history.len += 1;
-        }
+        // pending unstakes keep the end timestamps they were started with
+        ctx.accounts.staking.unstake_timelock = new_settings.unstake_timelock;
+        ctx.accounts.staking.instant_unstake = new_settings.instant_unstake;
+        ctx.accounts.staking.lock = new_settings.lock;
     }
 
     emit!(ChangeConfigEvent {
         id: ctx.accounts.staking.id,
         new_reward_params,
+        new_settings,
     });
 
     Ok(())
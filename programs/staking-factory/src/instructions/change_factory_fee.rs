@@ -0,0 +1,21 @@
+use crate::{error::*, event::*, state::*};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ChangeFactoryFee<'info> {
+    #[account(mut, seeds = [b"factory"], bump = factory.bump, has_one = authority)]
+    pub factory: Account<'info, Factory>,
+    pub authority: Signer<'info>,
+}
+
+pub fn change_factory_fee(ctx: Context<ChangeFactoryFee>, fee: u16) -> Result<()> {
+    if fee > Factory::MAX_FEE {
+        return err!(StakingError::FeeTooHigh);
+    }
+
+    ctx.accounts.factory.fee = fee;
+
+    emit!(ChangeFactoryFeeEvent { fee });
+
+    Ok(())
+}
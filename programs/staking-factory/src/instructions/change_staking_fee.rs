@@ -0,0 +1,28 @@
+use crate::{error::*, event::*, state::*};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ChangeStakingFee<'info> {
+    #[account(seeds = [b"factory"], bump = factory.bump, has_one = authority)]
+    pub factory: Account<'info, Factory>,
+    #[account(mut)]
+    pub staking: Account<'info, Staking>,
+    pub authority: Signer<'info>,
+}
+
+pub fn change_staking_fee(ctx: Context<ChangeStakingFee>, fee_override: Option<u16>) -> Result<()> {
+    if let Some(fee) = fee_override {
+        if fee > Factory::MAX_FEE {
+            return err!(StakingError::FeeTooHigh);
+        }
+    }
+
+    ctx.accounts.staking.fee_override = fee_override;
+
+    emit!(ChangeStakingFeeEvent {
+        id: ctx.accounts.staking.id,
+        fee_override,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use crate::{error::*, event::*, history::*, reward::*, state::*};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ChangeStreamConfig<'info> {
+    #[account(has_one = authority, constraint = !staking.closing @ StakingError::StakingClosing)]
+    pub staking: Account<'info, Staking>,
+    #[account(
+        mut,
+        seeds = [b"reward_stream", staking.key().as_ref(), reward_stream.index.to_le_bytes().as_ref()],
+        bump = reward_stream.bump,
+    )]
+    pub reward_stream: Account<'info, RewardStream>,
+    #[account(mut, seeds = [b"config_history", reward_stream.key().as_ref()], bump = config_history.bump)]
+    pub config_history: Box<Account<'info, ConfigHistory>>,
+    #[account(
+        mut,
+        seeds = [
+            b"config_history_page",
+            config_history.key().as_ref(),
+            &(config_history.pages_count - 1).to_le_bytes(),
+        ],
+        bump = last_page.bump,
+    )]
+    pub last_page: Box<Account<'info, ConfigHistoryPage>>,
+    /// CHECK: created if the last page is full, its address is checked then
+    #[account(mut)]
+    pub next_page: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn change_stream_config(
+    ctx: Context<ChangeStreamConfig>,
+    new_reward_params: RewardParams,
+) -> Result<()> {
+    let ts = Clock::get()?.unix_timestamp as u32;
+
+    if ts >= ctx.accounts.config_history.end_ts {
+        return err!(StakingError::StakingEnded);
+    }
+
+    new_reward_params.validate_fields()?;
+
+    change_reward_params(
+        &mut ctx.accounts.config_history,
+        &mut ctx.accounts.last_page,
+        &ctx.accounts.next_page,
+        &ctx.accounts.authority,
+        &ctx.accounts.system_program,
+        new_reward_params,
+        ts,
+    )?;
+    ctx.accounts.reward_stream.reward_params = new_reward_params;
+
+    emit!(ChangeStreamConfigEvent {
+        id: ctx.accounts.staking.id,
+        index: ctx.accounts.reward_stream.index,
+        new_reward_params,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,87 @@
+use crate::{error::*, event::*, reward::*, state::*};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+#[derive(Accounts)]
+pub struct ClaimPenaltyShare<'info> {
+    #[account(seeds = [b"factory"], bump = factory.bump)]
+    pub factory: Account<'info, Factory>,
+    #[account(
+        mut,
+        constraint = !staking.is_paused(&factory, PauseFlags::CLAIM) @ StakingError::Paused,
+    )]
+    pub staking: Account<'info, Staking>,
+    #[account(seeds = [b"config_history", staking.key().as_ref()], bump = config_history.bump)]
+    pub config_history: Box<Account<'info, ConfigHistory>>,
+    #[account(mut, associated_token::authority = staking, associated_token::mint = staking.stake_mint)]
+    pub staking_stake_vault: Account<'info, TokenAccount>,
+    pub beneficiary: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"member", staking.id.to_le_bytes().as_ref(), beneficiary.key().as_ref()],
+        bump = member.bump,
+    )]
+    pub member: Account<'info, Member>,
+    #[account(mut, associated_token::authority = member, associated_token::mint = staking.stake_mint)]
+    pub member_vault: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+fn transfer_to_member_vault(ctx: &Context<ClaimPenaltyShare>, amount: u64) -> Result<()> {
+    let signer: &[&[&[u8]]] = &[&[
+        b"staking".as_ref(),
+        &ctx.accounts.staking.id.to_le_bytes(),
+        &[ctx.accounts.staking.bump],
+    ]];
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.staking_stake_vault.to_account_info(),
+        to: ctx.accounts.member_vault.to_account_info(),
+        authority: ctx.accounts.staking.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        cpi_accounts,
+        signer,
+    );
+    token::transfer(cpi_ctx, amount)
+}
+
+/// config history pages and reward streams are passed in remaining accounts,
+/// see `settle_rewards`
+pub fn claim_penalty_share(ctx: Context<ClaimPenaltyShare>) -> Result<()> {
+    let ts = Clock::get()?.unix_timestamp as u32;
+
+    settle_rewards(
+        ts,
+        &mut ctx.accounts.staking,
+        &ctx.accounts.config_history,
+        &mut ctx.accounts.member,
+        ctx.remaining_accounts,
+    )?;
+
+    let amount = ctx.accounts.member.penalty_share_amount;
+    if amount == 0 {
+        return err!(StakingError::Zero);
+    }
+
+    transfer_to_member_vault(&ctx, amount)?;
+
+    ctx.accounts.member.penalty_share_amount = 0;
+    ctx.accounts.staking.penalty_shares_sum -= amount;
+    let available_amount = ctx
+        .accounts
+        .member
+        .available_amount
+        .checked_add(amount)
+        .ok_or(StakingError::Overflow)?;
+    ctx.accounts
+        .staking
+        .set_member_available(&mut ctx.accounts.member, available_amount)?;
+
+    emit!(ClaimPenaltyShareEvent {
+        beneficiary: ctx.accounts.beneficiary.key(),
+        amount,
+    });
+
+    Ok(())
+}
@@ -1,23 +1,23 @@
-use crate::{event::*, reward::*, state::*};
+use crate::{error::*, event::*, reward::*, state::*, token::*};
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount};
-
-const FACTORY_FEE_NUM: u64 = 3;
-const FACTORY_FEE_DENOM: u64 = 100;
 
 #[derive(Accounts)]
 pub struct ClaimReward<'info> {
     #[account(seeds = [b"factory"], bump = factory.bump)]
     pub factory: Account<'info, Factory>,
-    #[account(mut, token::authority = factory.authority, token::mint = staking.reward_mint)]
-    pub factory_vault: Account<'info, TokenAccount>,
+    /// CHECK: reward token account of the factory authority, checked in the handler
+    #[account(mut)]
+    pub factory_vault: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        constraint = !staking.is_paused(&factory, PauseFlags::CLAIM) @ StakingError::Paused,
+    )]
     pub staking: Account<'info, Staking>,
-    #[account(mut, associated_token::authority = staking, associated_token::mint = staking.reward_mint)]
-    pub staking_vault: Account<'info, TokenAccount>,
+    /// CHECK: associated reward token account of the staking, checked in the handler
+    #[account(mut)]
+    pub staking_vault: UncheckedAccount<'info>,
     #[account(seeds = [b"config_history", staking.key().as_ref()], bump = config_history.bump)]
     pub config_history: Box<Account<'info, ConfigHistory>>,
-    #[account(mut, seeds = [b"stakes_history", staking.key().as_ref()], bump = stakes_history.bump)]
-    pub stakes_history: Box<Account<'info, StakesHistory>>,
     pub beneficiary: Signer<'info>,
     #[account(
         mut,
@@ -25,9 +25,14 @@ pub struct ClaimReward<'info> {
         bump = member.bump,
     )]
     pub member: Account<'info, Member>,
+    /// CHECK: reward token account, checked by the token program
     #[account(mut)]
-    pub to: Account<'info, TokenAccount>,
-    pub token_program: Program<'info, Token>,
+    pub to: UncheckedAccount<'info>,
+    /// CHECK: the reward mint
+    #[account(address = staking.reward_mint)]
+    pub reward_mint: UncheckedAccount<'info>,
+    /// CHECK: token program or Token-2022 of the reward mint, checked in the handler
+    pub token_program: UncheckedAccount<'info>,
 }
 
 fn transfer_to_beneficiary(ctx: &Context<ClaimReward>, amount: u64) -> Result<()> {
@@ -36,16 +41,16 @@ fn transfer_to_beneficiary(ctx: &Context<ClaimReward>, amount: u64) -> Result<()
         &ctx.accounts.staking.id.to_le_bytes(),
         &[ctx.accounts.staking.bump],
     ]];
-    let cpi_ctx = CpiContext::new_with_signer(
-        ctx.accounts.token_program.to_account_info(),
-        token::Transfer {
-            from: ctx.accounts.staking_vault.to_account_info(),
-            to: ctx.accounts.to.to_account_info(),
-            authority: ctx.accounts.staking.to_account_info(),
-        },
+    transfer_checked(
+        &ctx.accounts.token_program,
+        &ctx.accounts.staking_vault,
+        &ctx.accounts.reward_mint,
+        &ctx.accounts.to,
+        &ctx.accounts.staking.to_account_info(),
         signer,
-    );
-    token::transfer(cpi_ctx, amount)
+        amount,
+        ctx.accounts.staking.reward_decimals,
+    )
 }
 
 fn transfer_to_factory_owner(ctx: &Context<ClaimReward>, amount: u64) -> Result<()> {
@@ -54,42 +59,78 @@ fn transfer_to_factory_owner(ctx: &Context<ClaimReward>, amount: u64) -> Result<
         &ctx.accounts.staking.id.to_le_bytes(),
         &[ctx.accounts.staking.bump],
     ]];
-    let cpi_ctx = CpiContext::new_with_signer(
-        ctx.accounts.token_program.to_account_info(),
-        token::Transfer {
-            from: ctx.accounts.staking_vault.to_account_info(),
-            to: ctx.accounts.factory_vault.to_account_info(),
-            authority: ctx.accounts.staking.to_account_info(),
-        },
+    transfer_checked(
+        &ctx.accounts.token_program,
+        &ctx.accounts.staking_vault,
+        &ctx.accounts.reward_mint,
+        &ctx.accounts.factory_vault,
+        &ctx.accounts.staking.to_account_info(),
         signer,
-    );
-    token::transfer(cpi_ctx, amount)
+        amount,
+        ctx.accounts.staking.reward_decimals,
+    )
 }
 
+/// config history pages and all reward streams are passed in remaining accounts,
+/// see `settle_rewards`. Reward streams are settled but claimed with `claim_stream_reward`
 pub fn claim_reward(ctx: Context<ClaimReward>) -> Result<()> {
     let ts = Clock::get()?.unix_timestamp as u32;
 
-    let rewards = calculate_rewards(
+    let reward_mint = ctx.accounts.staking.reward_mint;
+    load_associated_token_account(
+        &ctx.accounts.staking_vault,
+        &ctx.accounts.token_program,
+        &reward_mint,
+        &ctx.accounts.staking.key(),
+    )?;
+    load_token_account_of(
+        &ctx.accounts.factory_vault,
+        &ctx.accounts.token_program,
+        &reward_mint,
+        &ctx.accounts.factory.authority,
+    )?;
+
+    settle_rewards(
         ts,
-        &ctx.accounts.staking,
+        &mut ctx.accounts.staking,
         &ctx.accounts.config_history,
         &mut ctx.accounts.member,
-        &mut ctx.accounts.stakes_history,
+        ctx.remaining_accounts,
     )?;
-    ctx.accounts.member.rewards_amount += rewards;
 
-    let factory_fee = ctx.accounts.member.rewards_amount * FACTORY_FEE_NUM / FACTORY_FEE_DENOM;
+    let rewards_amount = ctx.accounts.member.rewards_amount;
+    if ctx.accounts.staking.total_funded - ctx.accounts.staking.total_claimed < rewards_amount {
+        return err!(StakingError::InsufficientFunding);
+    }
+
+    let fee = ctx
+        .accounts
+        .staking
+        .fee_override
+        .unwrap_or(ctx.accounts.factory.fee);
+    let factory_fee = rewards_amount
+        .checked_mul(fee as u64)
+        .ok_or(StakingError::Overflow)?
+        / Factory::FEE_DENOM;
     transfer_to_factory_owner(&ctx, factory_fee)?;
 
-    let amount_to_beneficiary = ctx.accounts.member.rewards_amount - factory_fee;
+    let amount_to_beneficiary = rewards_amount - factory_fee;
     transfer_to_beneficiary(&ctx, amount_to_beneficiary)?;
 
     ctx.accounts.member.rewards_amount = 0;
+    ctx.accounts.staking.unclaimed_rewards -= rewards_amount;
+    ctx.accounts.staking.total_claimed += rewards_amount;
+    ctx.accounts.staking.rewards_liability = ctx
+        .accounts
+        .staking
+        .rewards_liability
+        .saturating_sub(rewards_amount);
 
     emit!(ClaimRewardEvent {
         beneficiary: ctx.accounts.beneficiary.key(),
         amount_to_beneficiary,
         factory_fee,
+        fee,
     });
 
     Ok(())
@@ -0,0 +1,129 @@
+use crate::{error::*, event::*, history::*, reward::*, state::*};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount};
+
+#[derive(Accounts)]
+pub struct ClaimStreamReward<'info> {
+    #[account(seeds = [b"factory"], bump = factory.bump)]
+    pub factory: Account<'info, Factory>,
+    #[account(mut, token::authority = factory.authority, token::mint = reward_stream.reward_mint)]
+    pub factory_vault: Account<'info, TokenAccount>,
+    #[account(constraint = !staking.is_paused(&factory, PauseFlags::CLAIM) @ StakingError::Paused)]
+    pub staking: Account<'info, Staking>,
+    #[account(
+        mut,
+        seeds = [b"reward_stream", staking.key().as_ref(), reward_stream.index.to_le_bytes().as_ref()],
+        bump = reward_stream.bump,
+    )]
+    pub reward_stream: Account<'info, RewardStream>,
+    #[account(seeds = [b"config_history", reward_stream.key().as_ref()], bump = config_history.bump)]
+    pub config_history: Box<Account<'info, ConfigHistory>>,
+    #[account(mut, associated_token::authority = staking, associated_token::mint = reward_stream.reward_mint)]
+    pub stream_vault: Account<'info, TokenAccount>,
+    pub beneficiary: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"member", staking.id.to_le_bytes().as_ref(), beneficiary.key().as_ref()],
+        bump = member.bump,
+    )]
+    pub member: Account<'info, Member>,
+    #[account(mut)]
+    pub to: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+fn transfer_to_beneficiary(ctx: &Context<ClaimStreamReward>, amount: u64) -> Result<()> {
+    let signer: &[&[&[u8]]] = &[&[
+        b"staking".as_ref(),
+        &ctx.accounts.staking.id.to_le_bytes(),
+        &[ctx.accounts.staking.bump],
+    ]];
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        token::Transfer {
+            from: ctx.accounts.stream_vault.to_account_info(),
+            to: ctx.accounts.to.to_account_info(),
+            authority: ctx.accounts.staking.to_account_info(),
+        },
+        signer,
+    );
+    token::transfer(cpi_ctx, amount)
+}
+
+fn transfer_to_factory_owner(ctx: &Context<ClaimStreamReward>, amount: u64) -> Result<()> {
+    let signer: &[&[&[u8]]] = &[&[
+        b"staking".as_ref(),
+        &ctx.accounts.staking.id.to_le_bytes(),
+        &[ctx.accounts.staking.bump],
+    ]];
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        token::Transfer {
+            from: ctx.accounts.stream_vault.to_account_info(),
+            to: ctx.accounts.factory_vault.to_account_info(),
+            authority: ctx.accounts.staking.to_account_info(),
+        },
+        signer,
+    );
+    token::transfer(cpi_ctx, amount)
+}
+
+/// config history pages of the stream are passed in remaining accounts,
+/// claiming does not change the weighted amount so other streams are not settled
+pub fn claim_stream_reward(ctx: Context<ClaimStreamReward>) -> Result<()> {
+    let ts = Clock::get()?.unix_timestamp as u32;
+
+    let (configs, _) = load_configs(&ctx.accounts.config_history, ctx.remaining_accounts)?;
+    let rewards = calculate_stream_rewards(
+        ts,
+        &ctx.accounts.staking,
+        &mut ctx.accounts.reward_stream,
+        &configs,
+        &mut ctx.accounts.member,
+    )?;
+
+    let index = ctx.accounts.reward_stream.index as usize;
+    let rewards_amount = ctx.accounts.member.reward_streams[index]
+        .rewards_amount
+        .checked_add(rewards)
+        .ok_or(StakingError::Overflow)?;
+
+    let reward_stream = &ctx.accounts.reward_stream;
+    if reward_stream.total_funded - reward_stream.total_claimed < rewards_amount {
+        return err!(StakingError::InsufficientFunding);
+    }
+
+    let fee = ctx
+        .accounts
+        .staking
+        .fee_override
+        .unwrap_or(ctx.accounts.factory.fee);
+    let factory_fee = rewards_amount
+        .checked_mul(fee as u64)
+        .ok_or(StakingError::Overflow)?
+        / Factory::FEE_DENOM;
+    transfer_to_factory_owner(&ctx, factory_fee)?;
+
+    let amount_to_beneficiary = rewards_amount - factory_fee;
+    transfer_to_beneficiary(&ctx, amount_to_beneficiary)?;
+
+    // the rewards calculated here are claimed right away and never unclaimed
+    let unclaimed_rewards = ctx.accounts.member.reward_streams[index].rewards_amount;
+    ctx.accounts.member.reward_streams[index].rewards_amount = 0;
+    let reward_stream = &mut ctx.accounts.reward_stream;
+    reward_stream.unclaimed_rewards -= unclaimed_rewards;
+    reward_stream.total_claimed += rewards_amount;
+    reward_stream.rewards_liability = reward_stream
+        .rewards_liability
+        .saturating_sub(rewards_amount);
+
+    emit!(ClaimStreamRewardEvent {
+        beneficiary: ctx.accounts.beneficiary.key(),
+        index: ctx.accounts.reward_stream.index,
+        amount_to_beneficiary,
+        factory_fee,
+        fee,
+    });
+
+    Ok(())
+}
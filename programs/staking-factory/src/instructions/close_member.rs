@@ -0,0 +1,105 @@
+use crate::{error::*, event::*, reward::*, state::*};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, CloseAccount, Token, TokenAccount, Transfer};
+
+#[derive(Accounts)]
+pub struct CloseMember<'info> {
+    #[account(mut)]
+    pub staking: Account<'info, Staking>,
+    #[account(mut)]
+    pub beneficiary: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"member", staking.id.to_le_bytes().as_ref(), beneficiary.key().as_ref()],
+        bump = member.bump,
+        close = beneficiary,
+    )]
+    pub member: Account<'info, Member>,
+    #[account(mut, associated_token::authority = member, associated_token::mint = staking.stake_mint)]
+    pub member_vault: Account<'info, TokenAccount>,
+    /// receives tokens sent to the member vault directly
+    #[account(mut)]
+    pub to: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+fn is_empty(member: &Member) -> bool {
+    member.available_amount == 0
+        && member.stake_amount == 0
+        && member.pending_amount == 0
+        && member.rewards_amount == 0
+        && member.penalty_share_amount == 0
+        && member
+            .reward_streams
+            .iter()
+            .all(|stream| stream.rewards_amount == 0)
+}
+
+fn transfer_to_beneficiary(ctx: &Context<CloseMember>, amount: u64) -> Result<()> {
+    let signer: &[&[&[u8]]] = &[&[
+        b"member".as_ref(),
+        &ctx.accounts.staking.id.to_le_bytes(),
+        ctx.accounts.beneficiary.to_account_info().key.as_ref(),
+        &[ctx.accounts.member.bump],
+    ]];
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.member_vault.to_account_info(),
+            to: ctx.accounts.to.to_account_info(),
+            authority: ctx.accounts.member.to_account_info(),
+        },
+        signer,
+    );
+    token::transfer(cpi_ctx, amount)
+}
+
+fn close_member_vault(ctx: &Context<CloseMember>) -> Result<()> {
+    let signer: &[&[&[u8]]] = &[&[
+        b"member".as_ref(),
+        &ctx.accounts.staking.id.to_le_bytes(),
+        ctx.accounts.beneficiary.to_account_info().key.as_ref(),
+        &[ctx.accounts.member.bump],
+    ]];
+    let cpi_accounts = CloseAccount {
+        account: ctx.accounts.member_vault.to_account_info(),
+        destination: ctx.accounts.beneficiary.to_account_info(),
+        authority: ctx.accounts.member.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        cpi_accounts,
+        signer,
+    );
+    token::close_account(cpi_ctx)
+}
+
+/// all reward streams of the staking are passed in remaining accounts in index order
+pub fn close_member(ctx: Context<CloseMember>) -> Result<()> {
+    // stake changes settle all rewards, so nothing accrues to a member with no stake
+    if !is_empty(&ctx.accounts.member) {
+        return err!(StakingError::MemberNotEmpty);
+    }
+
+    // not accounted to the member, e.g. sent to the vault directly
+    let dust = ctx.accounts.member_vault.amount;
+    if dust != 0 {
+        transfer_to_beneficiary(&ctx, dust)?;
+    }
+    close_member_vault(&ctx)?;
+
+    ctx.accounts.staking.members_count -= 1;
+    // nothing is staked, so the reserves for the reward periods in progress are never paid
+    release_member_liability(&mut ctx.accounts.staking, &ctx.accounts.member)?;
+    release_member_stream_liabilities(
+        &ctx.accounts.staking,
+        &ctx.accounts.member,
+        ctx.remaining_accounts,
+    )?;
+
+    emit!(CloseMemberEvent {
+        beneficiary: ctx.accounts.beneficiary.key(),
+    });
+
+    Ok(())
+}
@@ -0,0 +1,158 @@
+use crate::{error::*, event::*, history::*, reward::*, state::*};
+use anchor_lang::{prelude::*, AccountsClose};
+use anchor_spl::{
+    associated_token::get_associated_token_address,
+    token::{self, Token, TokenAccount},
+};
+
+#[derive(Accounts)]
+pub struct CloseStaking<'info> {
+    #[account(
+        mut,
+        has_one = authority,
+        constraint = staking.closing @ StakingError::StakingNotClosing,
+        close = authority,
+    )]
+    pub staking: Account<'info, Staking>,
+    #[account(
+        mut,
+        seeds = [b"config_history", staking.key().as_ref()],
+        bump = config_history.bump,
+        close = authority,
+    )]
+    pub config_history: Box<Account<'info, ConfigHistory>>,
+    #[account(mut, associated_token::authority = staking, associated_token::mint = staking.reward_mint)]
+    pub staking_vault: Account<'info, TokenAccount>,
+    /// holds `Redistribute` penalties, same as `staking_vault` if the mints are equal
+    #[account(mut, associated_token::authority = staking, associated_token::mint = staking.stake_mint)]
+    pub stake_vault: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// receives the rewards left in the staking vault
+    #[account(mut)]
+    pub to: Account<'info, TokenAccount>,
+    /// receives the tokens left in the stake vault if it is not the staking vault
+    #[account(mut)]
+    pub stake_to: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+/// transfers everything left in the vault and closes it
+fn empty_vault<'info>(
+    ctx: &Context<'_, '_, '_, 'info, CloseStaking<'info>>,
+    vault: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+) -> Result<()> {
+    let signer: &[&[&[u8]]] = &[&[
+        b"staking".as_ref(),
+        &ctx.accounts.staking.id.to_le_bytes(),
+        &[ctx.accounts.staking.bump],
+    ]];
+
+    let amount = Account::<TokenAccount>::try_from(vault)?.amount;
+    if amount != 0 {
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            token::Transfer {
+                from: vault.clone(),
+                to: to.clone(),
+                authority: ctx.accounts.staking.to_account_info(),
+            },
+            signer,
+        );
+        token::transfer(cpi_ctx, amount)?;
+    }
+
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        token::CloseAccount {
+            account: vault.clone(),
+            destination: ctx.accounts.authority.to_account_info(),
+            authority: ctx.accounts.staking.to_account_info(),
+        },
+        signer,
+    );
+    token::close_account(cpi_ctx)
+}
+
+/// closes a reward stream from leading `(reward stream, config history,
+/// all config history pages.., stream vault, to)` accounts, returns the accounts after them
+fn close_reward_stream<'a, 'info>(
+    ctx: &Context<'_, '_, '_, 'info, CloseStaking<'info>>,
+    index: usize,
+    accounts: &'a [AccountInfo<'info>],
+) -> Result<&'a [AccountInfo<'info>]> {
+    let (reward_stream, config_history, _, _) =
+        load_reward_stream(&ctx.accounts.staking, accounts)?;
+    let authority = ctx.accounts.authority.to_account_info();
+    let accounts = close_history_pages(&config_history, &accounts[2..], &authority)?;
+    if accounts.len() < 2 {
+        return err!(StakingError::InvalidRewardStreams);
+    }
+    let (stream_vault, to) = (&accounts[0], &accounts[1]);
+
+    if reward_stream.unclaimed_rewards != 0 {
+        return err!(StakingError::MembersRemaining);
+    }
+    if reward_stream.index as usize != index
+        || stream_vault.key()
+            != get_associated_token_address(&ctx.accounts.staking.key(), &reward_stream.reward_mint)
+    {
+        return err!(StakingError::InvalidRewardStreams);
+    }
+
+    // a vault shared with the staking or a previous stream is emptied only once
+    if stream_vault.key() != ctx.accounts.staking_vault.key() && stream_vault.lamports() != 0 {
+        empty_vault(ctx, stream_vault, to)?;
+    }
+
+    reward_stream.close(authority.clone())?;
+    config_history.close(authority)?;
+
+    Ok(&accounts[2..])
+}
+
+/// all config and settings history pages are passed in remaining accounts,
+/// followed by the reward streams, see `close_reward_stream`
+pub fn close_staking<'info>(ctx: Context<'_, '_, '_, 'info, CloseStaking<'info>>) -> Result<()> {
+    // members left have nothing in the staking and may outlive it,
+    // so what is left in the vaults is excess
+    let staking = &ctx.accounts.staking;
+    if staking.stakes_sum != 0
+        || staking.pending_sum != 0
+        || staking.available_sum != 0
+        || staking.penalty_shares_sum != 0
+        || staking.unclaimed_rewards != 0
+    {
+        return err!(StakingError::MembersRemaining);
+    }
+
+    let mut accounts = close_history_pages(
+        &ctx.accounts.config_history,
+        ctx.remaining_accounts,
+        &ctx.accounts.authority.to_account_info(),
+    )?;
+    for index in 0..ctx.accounts.staking.reward_streams_count as usize {
+        accounts = close_reward_stream(&ctx, index, accounts)?;
+    }
+    if !accounts.is_empty() {
+        return err!(StakingError::InvalidRewardStreams);
+    }
+
+    empty_vault(
+        &ctx,
+        &ctx.accounts.staking_vault.to_account_info(),
+        &ctx.accounts.to.to_account_info(),
+    )?;
+    // a stake vault shared with a reward stream is already closed
+    let stake_vault = ctx.accounts.stake_vault.to_account_info();
+    if stake_vault.key() != ctx.accounts.staking_vault.key() && stake_vault.lamports() != 0 {
+        empty_vault(&ctx, &stake_vault, &ctx.accounts.stake_to.to_account_info())?;
+    }
+
+    emit!(CloseStakingEvent {
+        id: ctx.accounts.staking.id,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,126 @@
+use crate::{error::*, event::*, reward::*, state::*};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount};
+
+#[derive(Accounts)]
+pub struct Compound<'info> {
+    #[account(seeds = [b"factory"], bump = factory.bump)]
+    pub factory: Account<'info, Factory>,
+    #[account(mut, token::authority = factory.authority, token::mint = staking.reward_mint)]
+    pub factory_vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = staking.stake_mint == staking.reward_mint @ StakingError::MintsDiffer,
+        constraint = !staking.closing @ StakingError::StakingClosing,
+        constraint = !staking.is_paused(&factory, PauseFlags::CLAIM | PauseFlags::STAKE) @ StakingError::Paused,
+    )]
+    pub staking: Account<'info, Staking>,
+    #[account(mut, associated_token::authority = staking, associated_token::mint = staking.reward_mint)]
+    pub staking_vault: Account<'info, TokenAccount>,
+    #[account(seeds = [b"config_history", staking.key().as_ref()], bump = config_history.bump)]
+    pub config_history: Box<Account<'info, ConfigHistory>>,
+    /// beneficiary or anyone if the member enabled auto compounding
+    pub caller: Signer<'info>,
+    /// CHECK: only used for the member address
+    pub beneficiary: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"member", staking.id.to_le_bytes().as_ref(), beneficiary.key().as_ref()],
+        bump = member.bump,
+        constraint = caller.key() == beneficiary.key() || member.auto_compound @ StakingError::AutoCompoundDisabled,
+    )]
+    pub member: Account<'info, Member>,
+    #[account(mut, associated_token::authority = member, associated_token::mint = staking.stake_mint)]
+    pub member_vault: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+fn transfer_to_member_vault(ctx: &Context<Compound>, amount: u64) -> Result<()> {
+    let signer: &[&[&[u8]]] = &[&[
+        b"staking".as_ref(),
+        &ctx.accounts.staking.id.to_le_bytes(),
+        &[ctx.accounts.staking.bump],
+    ]];
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        token::Transfer {
+            from: ctx.accounts.staking_vault.to_account_info(),
+            to: ctx.accounts.member_vault.to_account_info(),
+            authority: ctx.accounts.staking.to_account_info(),
+        },
+        signer,
+    );
+    token::transfer(cpi_ctx, amount)
+}
+
+fn transfer_to_factory_owner(ctx: &Context<Compound>, amount: u64) -> Result<()> {
+    let signer: &[&[&[u8]]] = &[&[
+        b"staking".as_ref(),
+        &ctx.accounts.staking.id.to_le_bytes(),
+        &[ctx.accounts.staking.bump],
+    ]];
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        token::Transfer {
+            from: ctx.accounts.staking_vault.to_account_info(),
+            to: ctx.accounts.factory_vault.to_account_info(),
+            authority: ctx.accounts.staking.to_account_info(),
+        },
+        signer,
+    );
+    token::transfer(cpi_ctx, amount)
+}
+
+/// stakes rewards of a staking paying in its stake token
+pub fn compound(ctx: Context<Compound>) -> Result<()> {
+    let ts = Clock::get()?.unix_timestamp as u32;
+
+    settle_rewards(
+        ts,
+        &mut ctx.accounts.staking,
+        &ctx.accounts.config_history,
+        &mut ctx.accounts.member,
+        ctx.remaining_accounts,
+    )?;
+
+    let rewards_amount = ctx.accounts.member.rewards_amount;
+    if ctx.accounts.staking.total_funded - ctx.accounts.staking.total_claimed < rewards_amount {
+        return err!(StakingError::InsufficientFunding);
+    }
+
+    let fee = ctx
+        .accounts
+        .staking
+        .fee_override
+        .unwrap_or(ctx.accounts.factory.fee);
+    let factory_fee = rewards_amount
+        .checked_mul(fee as u64)
+        .ok_or(StakingError::Overflow)?
+        / Factory::FEE_DENOM;
+    transfer_to_factory_owner(&ctx, factory_fee)?;
+
+    let amount = rewards_amount - factory_fee;
+    transfer_to_member_vault(&ctx, amount)?;
+
+    ctx.accounts.member.rewards_amount = 0;
+    ctx.accounts.staking.unclaimed_rewards -= rewards_amount;
+    ctx.accounts.staking.total_claimed += rewards_amount;
+    ctx.accounts.staking.rewards_liability = ctx
+        .accounts
+        .staking
+        .rewards_liability
+        .saturating_sub(rewards_amount);
+
+    ctx.accounts
+        .staking
+        .add_member_stake(&mut ctx.accounts.member, amount, ts)?;
+
+    emit!(CompoundEvent {
+        beneficiary: ctx.accounts.beneficiary.key(),
+        amount,
+        factory_fee,
+        fee,
+    });
+
+    Ok(())
+}
@@ -1,6 +1,6 @@
-use crate::{event::*, reward::RewardParams, state::*};
+use crate::{error::*, event::*, reward::RewardParams, state::*, token::*};
 use anchor_lang::prelude::*;
-use anchor_spl::token::Token;
+use anchor_spl::associated_token::AssociatedToken;
 
 #[derive(Accounts)]
 pub struct CreateStaking<'info> {
@@ -20,47 +20,128 @@ pub struct CreateStaking<'info> {
         seeds = [b"config_history", staking.key().as_ref()],
         bump,
         space = 8 + ConfigHistory::LEN,
-   )]
+    )]
     pub config_history: Box<Account<'info, ConfigHistory>>,
     #[account(
         init,
         payer = authority,
-        seeds = [b"stakes_history", staking.key().as_ref()],
+        seeds = [b"config_history_page", config_history.key().as_ref(), &0u32.to_le_bytes()],
+        bump,
+        space = 8 + ConfigHistoryPage::LEN,
+    )]
+    pub config_history_page: Box<Account<'info, ConfigHistoryPage>>,
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"settings_history_page", config_history.key().as_ref(), &0u32.to_le_bytes()],
         bump,
-        space = 8 + StakesHistory::LEN,
+        space = 8 + SettingsHistoryPage::LEN,
     )]
-    pub stakes_history: Box<Account<'info, StakesHistory>>,
+    pub settings_history_page: Box<Account<'info, SettingsHistoryPage>>,
+    /// CHECK: mint of `stake_token_program`, checked in the handler
+    pub stake_mint: UncheckedAccount<'info>,
+    /// CHECK: mint of `token_program`, checked in the handler
+    pub reward_mint: UncheckedAccount<'info>,
+    // anyone can create the staking's token accounts beforehand, the existing ones
+    // are checked to be the associated accounts of the mints
+    /// CHECK: associated token account of the staking for `reward_mint`, created in the handler
+    #[account(mut)]
+    pub staking_vault: UncheckedAccount<'info>,
+    /// CHECK: associated token account of the staking for `stake_mint`, created in the handler.
+    /// Receives `Redistribute` penalties, same as `staking_vault` if the mints are equal
+    #[account(mut)]
+    pub stake_vault: UncheckedAccount<'info>,
     #[account(mut)]
     pub authority: Signer<'info>,
-    pub token_program: Program<'info, Token>,
+    /// CHECK: token program or Token-2022 of `reward_mint`, checked in the handler
+    pub token_program: UncheckedAccount<'info>,
+    /// CHECK: token program or Token-2022 of `stake_mint`, checked in the handler
+    pub stake_token_program: UncheckedAccount<'info>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
 }
 
-pub fn create_staking(
-    ctx: Context<CreateStaking>,
-    stake_mint: Pubkey,
-    reward_mint: Pubkey,
-    unstake_timelock: u32,
+fn init_vault<'info>(
+    ctx: &Context<'_, '_, '_, 'info, CreateStaking<'info>>,
+    vault: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+) -> Result<()> {
+    init_associated_token_account(
+        &ctx.accounts.authority.to_account_info(),
+        vault,
+        &ctx.accounts.staking.to_account_info(),
+        mint,
+        &ctx.accounts.system_program.to_account_info(),
+        token_program,
+        &ctx.accounts.associated_token_program.to_account_info(),
+        &ctx.accounts.rent.to_account_info(),
+    )
+}
+
+pub fn create_staking<'info>(
+    ctx: Context<'_, '_, '_, 'info, CreateStaking<'info>>,
     reward_params: RewardParams,
+    settings: PoolSettings,
+    start_ts: Option<u32>,
+    end_ts: Option<u32>,
 ) -> Result<()> {
     let ts = Clock::get()?.unix_timestamp as u32;
 
     reward_params.validate_fields()?;
 
+    let start_ts = start_ts.map_or(ts, |start_ts| start_ts.max(ts));
+    let end_ts = end_ts.unwrap_or(u32::MAX);
+    if end_ts <= start_ts {
+        return err!(StakingError::InvalidEndTs);
+    }
+
+    settings.validate_fields()?;
+
+    let stake_mint = load_mint(&ctx.accounts.stake_mint, &ctx.accounts.stake_token_program)?;
+    let reward_mint = load_mint(&ctx.accounts.reward_mint, &ctx.accounts.token_program)?;
+    check_mint_extensions(&ctx.accounts.stake_mint)?;
+    check_mint_extensions(&ctx.accounts.reward_mint)?;
+    init_vault(
+        &ctx,
+        &ctx.accounts.staking_vault.to_account_info(),
+        &ctx.accounts.reward_mint.to_account_info(),
+        &ctx.accounts.token_program.to_account_info(),
+    )?;
+    init_vault(
+        &ctx,
+        &ctx.accounts.stake_vault.to_account_info(),
+        &ctx.accounts.stake_mint.to_account_info(),
+        &ctx.accounts.stake_token_program.to_account_info(),
+    )?;
+
     ctx.accounts.staking.bump = *ctx.bumps.get("staking").unwrap();
     ctx.accounts.staking.authority = ctx.accounts.authority.key();
     ctx.accounts.staking.id = ctx.accounts.factory.stakings_count;
-    ctx.accounts.staking.stake_mint = stake_mint;
-    ctx.accounts.staking.reward_mint = reward_mint;
-    ctx.accounts.staking.unstake_timelock = unstake_timelock;
+    ctx.accounts.staking.stake_mint = ctx.accounts.stake_mint.key();
+    ctx.accounts.staking.reward_mint = ctx.accounts.reward_mint.key();
+    ctx.accounts.staking.stake_decimals = stake_mint.decimals;
+    ctx.accounts.staking.reward_decimals = reward_mint.decimals;
+    ctx.accounts.staking.unstake_timelock = settings.unstake_timelock;
     ctx.accounts.staking.reward_params = reward_params;
+    ctx.accounts.staking.reward_per_share_ts = start_ts;
+    ctx.accounts.staking.instant_unstake = settings.instant_unstake;
+    ctx.accounts.staking.lock = settings.lock;
 
     ctx.accounts.config_history.bump = *ctx.bumps.get("config_history").unwrap();
-    ctx.accounts.config_history.len = 1;
-    ctx.accounts.config_history.reward_params[0] = reward_params;
-    ctx.accounts.config_history.start_timestamps[0] = ts;
+    ctx.accounts.config_history.start_ts = start_ts;
+    ctx.accounts.config_history.end_ts = end_ts;
+    ctx.accounts.config_history.pages_count = 1;
+    ctx.accounts.config_history.settings_pages_count = 1;
+
+    ctx.accounts.config_history_page.bump = *ctx.bumps.get("config_history_page").unwrap();
+    ctx.accounts.config_history_page.reward_params = vec![reward_params];
+    ctx.accounts.config_history_page.start_timestamps = vec![start_ts];
 
-    ctx.accounts.stakes_history.bump = *ctx.bumps.get("stakes_history").unwrap();
+    ctx.accounts.settings_history_page.bump = *ctx.bumps.get("settings_history_page").unwrap();
+    ctx.accounts.settings_history_page.settings = vec![settings];
+    ctx.accounts.settings_history_page.timestamps = vec![ts];
 
     ctx.accounts.factory.stakings_count += 1;
 
@@ -68,6 +149,9 @@ pub fn create_staking(
         id: ctx.accounts.staking.id,
         authority: ctx.accounts.staking.authority,
         reward_params,
+        start_ts,
+        end_ts,
+        settings,
     });
 
     Ok(())
@@ -1,9 +1,14 @@
-use crate::{event::*, state::*};
+use crate::{error::*, event::*, state::*, token::*};
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
 
 #[derive(Accounts)]
 pub struct Deposit<'info> {
+    #[account(seeds = [b"factory"], bump = factory.bump)]
+    pub factory: Account<'info, Factory>,
+    #[account(
+        mut,
+        constraint = !staking.is_paused(&factory, PauseFlags::DEPOSIT) @ StakingError::Paused,
+    )]
     pub staking: Account<'info, Staking>,
     pub beneficiary: Signer<'info>,
     #[account(
@@ -12,34 +17,72 @@ pub struct Deposit<'info> {
         bump = member.bump,
     )]
     pub member: Account<'info, Member>,
+    /// CHECK: stake token account of the beneficiary, checked by the token program
     #[account(mut)]
-    pub from: Account<'info, TokenAccount>,
-    #[account(mut, associated_token::authority = member, associated_token::mint = staking.stake_mint)]
-    pub member_vault: Account<'info, TokenAccount>,
-    pub token_program: Program<'info, Token>,
-}
-
-fn transfer_to_member_vault(ctx: &Context<Deposit>, amount: u64) -> Result<()> {
-    let cpi_ctx = CpiContext::new(
-        ctx.accounts.token_program.to_account_info(),
-        Transfer {
-            from: ctx.accounts.from.to_account_info(),
-            to: ctx.accounts.member_vault.to_account_info(),
-            authority: ctx.accounts.beneficiary.to_account_info(),
-        },
-    );
-    token::transfer(cpi_ctx, amount)
+    pub from: UncheckedAccount<'info>,
+    /// CHECK: associated stake token account of the member, checked in the handler
+    #[account(mut)]
+    pub member_vault: UncheckedAccount<'info>,
+    /// CHECK: the stake mint
+    #[account(address = staking.stake_mint)]
+    pub stake_mint: UncheckedAccount<'info>,
+    /// CHECK: token program or Token-2022 of the stake mint, checked in the handler
+    pub token_program: UncheckedAccount<'info>,
 }
 
-pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
-    transfer_to_member_vault(&ctx, amount)?;
+/// shared with `deposit_and_stake`, returns the amount received
+/// which is less than `amount` for mints with transfer fees
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn deposit_to_member_vault<'info>(
+    token_program: &AccountInfo<'info>,
+    staking: &mut Staking,
+    beneficiary: &Signer<'info>,
+    from: &AccountInfo<'info>,
+    member_vault: &AccountInfo<'info>,
+    stake_mint: &AccountInfo<'info>,
+    member: &mut Account<'info, Member>,
+    amount: u64,
+) -> Result<u64> {
+    let vault_amount =
+        load_associated_token_account(member_vault, token_program, stake_mint.key, &member.key())?
+            .amount;
+    transfer_checked(
+        token_program,
+        from,
+        stake_mint,
+        member_vault,
+        &beneficiary.to_account_info(),
+        &[],
+        amount,
+        staking.stake_decimals,
+    )?;
+    let amount = load_token_account(member_vault, token_program)?.amount - vault_amount;
 
-    ctx.accounts.member.available_amount += amount;
+    let available_amount = member
+        .available_amount
+        .checked_add(amount)
+        .ok_or(StakingError::Overflow)?;
+    staking.set_member_available(member, available_amount)?;
 
     emit!(DepositEvent {
-        beneficiary: ctx.accounts.beneficiary.key(),
+        beneficiary: beneficiary.key(),
         amount,
     });
 
+    Ok(amount)
+}
+
+pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+    deposit_to_member_vault(
+        &ctx.accounts.token_program,
+        &mut ctx.accounts.staking,
+        &ctx.accounts.beneficiary,
+        &ctx.accounts.from,
+        &ctx.accounts.member_vault,
+        &ctx.accounts.stake_mint,
+        &mut ctx.accounts.member,
+        amount,
+    )?;
+
     Ok(())
 }
@@ -0,0 +1,61 @@
+use crate::{
+    error::*,
+    instructions::{deposit::deposit_to_member_vault, stake::stake_available},
+    state::*,
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct DepositAndStake<'info> {
+    #[account(seeds = [b"factory"], bump = factory.bump)]
+    pub factory: Account<'info, Factory>,
+    #[account(
+        mut,
+        constraint = !staking.closing @ StakingError::StakingClosing,
+        constraint = !staking.is_paused(&factory, PauseFlags::DEPOSIT | PauseFlags::STAKE) @ StakingError::Paused,
+    )]
+    pub staking: Account<'info, Staking>,
+    #[account(seeds = [b"config_history", staking.key().as_ref()], bump = config_history.bump)]
+    pub config_history: Box<Account<'info, ConfigHistory>>,
+    pub beneficiary: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"member", staking.id.to_le_bytes().as_ref(), beneficiary.key().as_ref()],
+        bump = member.bump,
+    )]
+    pub member: Account<'info, Member>,
+    /// CHECK: stake token account of the beneficiary, checked by the token program
+    #[account(mut)]
+    pub from: UncheckedAccount<'info>,
+    /// CHECK: associated stake token account of the member, checked in the handler
+    #[account(mut)]
+    pub member_vault: UncheckedAccount<'info>,
+    /// CHECK: the stake mint
+    #[account(address = staking.stake_mint)]
+    pub stake_mint: UncheckedAccount<'info>,
+    /// CHECK: token program or Token-2022 of the stake mint, checked in the handler
+    pub token_program: UncheckedAccount<'info>,
+}
+
+/// stakes the amount received, see `deposit_to_member_vault`
+pub fn deposit_and_stake(ctx: Context<DepositAndStake>, amount: u64) -> Result<()> {
+    let amount = deposit_to_member_vault(
+        &ctx.accounts.token_program,
+        &mut ctx.accounts.staking,
+        &ctx.accounts.beneficiary,
+        &ctx.accounts.from,
+        &ctx.accounts.member_vault,
+        &ctx.accounts.stake_mint,
+        &mut ctx.accounts.member,
+        amount,
+    )?;
+
+    stake_available(
+        &mut ctx.accounts.staking,
+        &ctx.accounts.config_history,
+        &mut ctx.accounts.member,
+        ctx.remaining_accounts,
+        ctx.accounts.beneficiary.key(),
+        amount,
+    )
+}
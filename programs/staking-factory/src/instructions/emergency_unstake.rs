@@ -0,0 +1,64 @@
+use crate::{error::*, event::*, reward::*, state::*};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct EmergencyUnstake<'info> {
+    #[account(
+        mut,
+        constraint = staking.emergency_unstake @ StakingError::EmergencyUnstakeDisabled,
+    )]
+    pub staking: Account<'info, Staking>,
+    pub beneficiary: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"member", staking.id.to_le_bytes().as_ref(), beneficiary.key().as_ref()],
+        bump = member.bump,
+    )]
+    pub member: Account<'info, Member>,
+}
+
+/// Makes the whole stake and pending unstakes available ignoring timelocks and locks.
+/// Rewards are not calculated so that it works even if that fails, unclaimed rewards are forfeited.
+/// All reward streams of the staking may be passed in remaining accounts in index order
+/// to forfeit their rewards and release their liabilities too, otherwise these are kept
+pub fn emergency_unstake(ctx: Context<EmergencyUnstake>) -> Result<()> {
+    let amount = ctx.accounts.member.stake_amount + ctx.accounts.member.pending_amount;
+    if amount == 0 {
+        return err!(StakingError::Zero);
+    }
+
+    // released with the weighted amount the rewards accrued to
+    release_member_liability(&mut ctx.accounts.staking, &ctx.accounts.member)?;
+    if !ctx.remaining_accounts.is_empty() {
+        release_member_stream_liabilities(
+            &ctx.accounts.staking,
+            &ctx.accounts.member,
+            ctx.remaining_accounts,
+        )?;
+        ctx.accounts.member.reward_streams = Default::default();
+    }
+
+    let staking = &mut ctx.accounts.staking;
+    let member = &mut ctx.accounts.member;
+    staking.set_member_stake(member, 0, LockParams::MULTIPLIER_DENOM as u32)?;
+    member.lock_end_ts = 0;
+    member.pending_unstakes = Default::default();
+    staking.set_member_pending(member, 0)?;
+    let available_amount = member
+        .available_amount
+        .checked_add(amount)
+        .ok_or(StakingError::Overflow)?;
+    staking.set_member_available(member, available_amount)?;
+
+    let forfeited_rewards = member.rewards_amount;
+    member.rewards_amount = 0;
+    member.fixed_reserve = 0;
+
+    emit!(EmergencyUnstakeEvent {
+        beneficiary: ctx.accounts.beneficiary.key(),
+        amount,
+        forfeited_rewards,
+    });
+
+    Ok(())
+}
@@ -3,32 +3,65 @@ use anchor_lang::prelude::*;
 
 #[derive(Accounts)]
 pub struct EndUnstake<'info> {
+    #[account(seeds = [b"factory"], bump = factory.bump)]
+    pub factory: Account<'info, Factory>,
+    #[account(
+        mut,
+        constraint = !staking.is_paused(&factory, PauseFlags::UNSTAKE) @ StakingError::Paused,
+    )]
     pub staking: Account<'info, Staking>,
     pub beneficiary: Signer<'info>,
     #[account(
         mut,
         seeds = [b"member", staking.id.to_le_bytes().as_ref(), beneficiary.key().as_ref()],
         bump = member.bump,
-        constraint = member.pending_unstake_active @ StakingError::UnstakeInactive,
+        constraint = member.pending_amount != 0 @ StakingError::UnstakeInactive,
     )]
     pub member: Account<'info, Member>,
 }
 
-pub fn end_unstake(ctx: Context<EndUnstake>) -> Result<()> {
+/// releases matured pending unstakes, shared with `end_unstake_and_withdraw`
+pub(crate) fn release_pending_unstakes(
+    staking: &mut Staking,
+    member: &mut Member,
+    beneficiary: Pubkey,
+) -> Result<u64> {
     let ts = Clock::get()?.unix_timestamp as u32;
 
-    if ctx.accounts.member.pending_unstake_end_ts > ts {
-        return err!(StakingError::UnstakeTimelock);
+    let mut amount = 0;
+    for pending_unstake in member.pending_unstakes.iter_mut() {
+        if pending_unstake.amount != 0 && pending_unstake.end_ts <= ts {
+            amount += pending_unstake.amount;
+            *pending_unstake = PendingUnstake::default();
+        }
     }
 
-    ctx.accounts.member.available_amount += ctx.accounts.member.pending_amount;
-    ctx.accounts.member.pending_amount = 0;
+    if amount == 0 {
+        return err!(StakingError::UnstakeTimelock);
+    }
 
-    ctx.accounts.member.pending_unstake_active = false;
+    let available_amount = member
+        .available_amount
+        .checked_add(amount)
+        .ok_or(StakingError::Overflow)?;
+    staking.set_member_available(member, available_amount)?;
+    let pending_amount = member.pending_amount - amount;
+    staking.set_member_pending(member, pending_amount)?;
 
     emit!(EndUnstakeEvent {
-        beneficiary: ctx.accounts.beneficiary.key(),
+        beneficiary,
+        amount,
     });
 
+    Ok(amount)
+}
+
+pub fn end_unstake(ctx: Context<EndUnstake>) -> Result<()> {
+    release_pending_unstakes(
+        &mut ctx.accounts.staking,
+        &mut ctx.accounts.member,
+        ctx.accounts.beneficiary.key(),
+    )?;
+
     Ok(())
 }
@@ -0,0 +1,56 @@
+use crate::{
+    error::*,
+    instructions::{end_unstake::release_pending_unstakes, withdraw::withdraw_from_member_vault},
+    state::*,
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct EndUnstakeAndWithdraw<'info> {
+    #[account(seeds = [b"factory"], bump = factory.bump)]
+    pub factory: Account<'info, Factory>,
+    #[account(
+        mut,
+        constraint = !staking.is_paused(&factory, PauseFlags::UNSTAKE) @ StakingError::Paused,
+    )]
+    pub staking: Account<'info, Staking>,
+    pub beneficiary: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"member", staking.id.to_le_bytes().as_ref(), beneficiary.key().as_ref()],
+        bump = member.bump,
+        constraint = member.pending_amount != 0 @ StakingError::UnstakeInactive,
+    )]
+    pub member: Account<'info, Member>,
+    /// CHECK: associated stake token account of the member, checked in the handler
+    #[account(mut)]
+    pub member_vault: UncheckedAccount<'info>,
+    /// CHECK: stake token account, checked by the token program
+    #[account(mut)]
+    pub to: UncheckedAccount<'info>,
+    /// CHECK: the stake mint
+    #[account(address = staking.stake_mint)]
+    pub stake_mint: UncheckedAccount<'info>,
+    /// CHECK: token program or Token-2022 of the stake mint, checked in the handler
+    pub token_program: UncheckedAccount<'info>,
+}
+
+/// withdraws exactly the amount released from the timelock
+pub fn end_unstake_and_withdraw(ctx: Context<EndUnstakeAndWithdraw>) -> Result<()> {
+    let amount = release_pending_unstakes(
+        &mut ctx.accounts.staking,
+        &mut ctx.accounts.member,
+        ctx.accounts.beneficiary.key(),
+    )?;
+
+    withdraw_from_member_vault(
+        &ctx.accounts.token_program,
+        &mut ctx.accounts.staking,
+        &ctx.accounts.beneficiary,
+        &mut ctx.accounts.member,
+        &ctx.accounts.member_vault,
+        &ctx.accounts.to,
+        &ctx.accounts.stake_mint,
+        amount,
+    )
+}
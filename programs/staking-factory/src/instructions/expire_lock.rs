@@ -0,0 +1,48 @@
+use crate::{error::*, event::*, reward::*, state::*};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ExpireLock<'info> {
+    #[account(seeds = [b"factory"], bump = factory.bump)]
+    pub factory: Account<'info, Factory>,
+    #[account(
+        mut,
+        constraint = !staking.is_paused(&factory, PauseFlags::STAKE) @ StakingError::Paused,
+    )]
+    pub staking: Account<'info, Staking>,
+    #[account(seeds = [b"config_history", staking.key().as_ref()], bump = config_history.bump)]
+    pub config_history: Box<Account<'info, ConfigHistory>>,
+    /// CHECK: only derives the member's address
+    pub beneficiary: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"member", staking.id.to_le_bytes().as_ref(), beneficiary.key().as_ref()],
+        bump = member.bump,
+    )]
+    pub member: Account<'info, Member>,
+}
+
+/// Resets the lock multiplier of a member whose lock is over, callable by anyone
+/// so that stale multipliers do not dilute the rewards of other members.
+/// Config history pages and reward streams are passed in remaining accounts, see `settle_rewards`
+pub fn expire_lock(ctx: Context<ExpireLock>) -> Result<()> {
+    let ts = Clock::get()?.unix_timestamp as u32;
+
+    if ts < ctx.accounts.member.lock_end_ts {
+        return err!(StakingError::StakeLocked);
+    }
+
+    settle_rewards(
+        ts,
+        &mut ctx.accounts.staking,
+        &ctx.accounts.config_history,
+        &mut ctx.accounts.member,
+        ctx.remaining_accounts,
+    )?;
+
+    emit!(ExpireLockEvent {
+        beneficiary: ctx.accounts.beneficiary.key(),
+    });
+
+    Ok(())
+}
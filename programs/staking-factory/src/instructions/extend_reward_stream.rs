@@ -0,0 +1,39 @@
+use crate::{error::*, event::*, state::*};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ExtendRewardStream<'info> {
+    #[account(has_one = authority, constraint = !staking.closing @ StakingError::StakingClosing)]
+    pub staking: Account<'info, Staking>,
+    #[account(
+        seeds = [b"reward_stream", staking.key().as_ref(), reward_stream.index.to_le_bytes().as_ref()],
+        bump = reward_stream.bump,
+    )]
+    pub reward_stream: Account<'info, RewardStream>,
+    #[account(mut, seeds = [b"config_history", reward_stream.key().as_ref()], bump = config_history.bump)]
+    pub config_history: Box<Account<'info, ConfigHistory>>,
+    pub authority: Signer<'info>,
+}
+
+pub fn extend_reward_stream(ctx: Context<ExtendRewardStream>, end_ts: u32) -> Result<()> {
+    let ts = Clock::get()?.unix_timestamp as u32;
+
+    // same as `extend_staking`
+    if ts >= ctx.accounts.config_history.end_ts {
+        return err!(StakingError::StakingEnded);
+    }
+
+    if end_ts <= ctx.accounts.config_history.end_ts {
+        return err!(StakingError::InvalidEndTs);
+    }
+
+    ctx.accounts.config_history.end_ts = end_ts;
+
+    emit!(ExtendRewardStreamEvent {
+        id: ctx.accounts.staking.id,
+        index: ctx.accounts.reward_stream.index,
+        end_ts,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,33 @@
+use crate::{error::*, event::*, state::*};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ExtendStaking<'info> {
+    #[account(has_one = authority, constraint = !staking.closing @ StakingError::StakingClosing)]
+    pub staking: Account<'info, Staking>,
+    #[account(mut, seeds = [b"config_history", staking.key().as_ref()], bump = config_history.bump)]
+    pub config_history: Box<Account<'info, ConfigHistory>>,
+    pub authority: Signer<'info>,
+}
+
+pub fn extend_staking(ctx: Context<ExtendStaking>, end_ts: u32) -> Result<()> {
+    let ts = Clock::get()?.unix_timestamp as u32;
+
+    // resuming an ended staking would pay rewards for the time it was ended
+    if ts >= ctx.accounts.config_history.end_ts {
+        return err!(StakingError::StakingEnded);
+    }
+
+    if end_ts <= ctx.accounts.config_history.end_ts {
+        return err!(StakingError::InvalidEndTs);
+    }
+
+    ctx.accounts.config_history.end_ts = end_ts;
+
+    emit!(ExtendStakingEvent {
+        id: ctx.accounts.staking.id,
+        end_ts,
+    });
+
+    Ok(())
+}
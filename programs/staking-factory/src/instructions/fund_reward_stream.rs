@@ -0,0 +1,50 @@
+use crate::{event::*, state::*};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+#[derive(Accounts)]
+pub struct FundRewardStream<'info> {
+    pub staking: Account<'info, Staking>,
+    #[account(
+        mut,
+        seeds = [b"reward_stream", staking.key().as_ref(), reward_stream.index.to_le_bytes().as_ref()],
+        bump = reward_stream.bump,
+    )]
+    pub reward_stream: Account<'info, RewardStream>,
+    #[account(mut, associated_token::authority = staking, associated_token::mint = reward_stream.reward_mint)]
+    pub stream_vault: Account<'info, TokenAccount>,
+    pub funder: Signer<'info>,
+    #[account(mut)]
+    pub from: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+fn transfer_to_stream_vault(ctx: &Context<FundRewardStream>, amount: u64) -> Result<()> {
+    let cpi_ctx = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.from.to_account_info(),
+            to: ctx.accounts.stream_vault.to_account_info(),
+            authority: ctx.accounts.funder.to_account_info(),
+        },
+    );
+    token::transfer(cpi_ctx, amount)
+}
+
+pub fn fund_reward_stream(ctx: Context<FundRewardStream>, amount: u64) -> Result<()> {
+    transfer_to_stream_vault(&ctx, amount)?;
+
+    ctx.accounts.reward_stream.total_funded += amount;
+
+    emit!(FundRewardStreamEvent {
+        id: ctx.accounts.staking.id,
+        index: ctx.accounts.reward_stream.index,
+        funder: ctx.accounts.funder.key(),
+        amount,
+        total_funded: ctx.accounts.reward_stream.total_funded,
+        total_claimed: ctx.accounts.reward_stream.total_claimed,
+        rewards_liability: ctx.accounts.reward_stream.rewards_liability,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use crate::{error::*, event::*, state::*, token::*};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct FundRewards<'info> {
+    #[account(mut)]
+    pub staking: Account<'info, Staking>,
+    /// CHECK: associated reward token account of the staking, checked in the handler
+    #[account(mut)]
+    pub staking_vault: UncheckedAccount<'info>,
+    pub funder: Signer<'info>,
+    /// CHECK: reward token account of the funder, checked by the token program
+    #[account(mut)]
+    pub from: UncheckedAccount<'info>,
+    /// CHECK: the reward mint
+    #[account(address = staking.reward_mint)]
+    pub reward_mint: UncheckedAccount<'info>,
+    /// CHECK: token program or Token-2022 of the reward mint, checked in the handler
+    pub token_program: UncheckedAccount<'info>,
+}
+
+fn transfer_to_staking_vault(ctx: &Context<FundRewards>, amount: u64) -> Result<()> {
+    transfer_checked(
+        &ctx.accounts.token_program,
+        &ctx.accounts.from,
+        &ctx.accounts.reward_mint,
+        &ctx.accounts.staking_vault,
+        &ctx.accounts.funder.to_account_info(),
+        &[],
+        amount,
+        ctx.accounts.staking.reward_decimals,
+    )
+}
+
+/// funds the amount received, which is less than `amount` for mints with transfer fees
+pub fn fund_rewards(ctx: Context<FundRewards>, amount: u64) -> Result<()> {
+    let vault_amount = load_associated_token_account(
+        &ctx.accounts.staking_vault,
+        &ctx.accounts.token_program,
+        &ctx.accounts.staking.reward_mint,
+        &ctx.accounts.staking.key(),
+    )?
+    .amount;
+    transfer_to_staking_vault(&ctx, amount)?;
+    let amount = load_token_account(&ctx.accounts.staking_vault, &ctx.accounts.token_program)?
+        .amount
+        - vault_amount;
+
+    ctx.accounts.staking.total_funded = ctx
+        .accounts
+        .staking
+        .total_funded
+        .checked_add(amount)
+        .ok_or(StakingError::Overflow)?;
+
+    emit!(FundRewardsEvent {
+        id: ctx.accounts.staking.id,
+        funder: ctx.accounts.funder.key(),
+        amount,
+        total_funded: ctx.accounts.staking.total_funded,
+        total_claimed: ctx.accounts.staking.total_claimed,
+        rewards_liability: ctx.accounts.staking.rewards_liability,
+    });
+
+    Ok(())
+}
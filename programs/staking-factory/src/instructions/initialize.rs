@@ -1,4 +1,4 @@
-use crate::{event::*, state::*};
+use crate::{error::*, event::*, state::*};
 use anchor_lang::prelude::*;
 
 #[derive(Accounts)]
@@ -10,11 +10,16 @@ pub struct Initialize<'info> {
     pub system_program: Program<'info, System>,
 }
 
-pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
+pub fn initialize(ctx: Context<Initialize>, fee: u16) -> Result<()> {
+    if fee > Factory::MAX_FEE {
+        return err!(StakingError::FeeTooHigh);
+    }
+
     ctx.accounts.factory.bump = *ctx.bumps.get("factory").unwrap();
     ctx.accounts.factory.authority = ctx.accounts.authority.key();
+    ctx.accounts.factory.fee = fee;
 
-    emit!(InitializeEvent {});
+    emit!(InitializeEvent { fee });
 
     Ok(())
 }
@@ -0,0 +1,173 @@
+use crate::{error::*, event::*, reward::*, state::*};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::get_associated_token_address,
+    token::{self, Token, TokenAccount},
+};
+
+#[derive(Accounts)]
+pub struct InstantUnstake<'info> {
+    #[account(seeds = [b"factory"], bump = factory.bump)]
+    pub factory: Account<'info, Factory>,
+    #[account(
+        mut,
+        constraint = !staking.is_paused(&factory, PauseFlags::UNSTAKE) @ StakingError::Paused,
+    )]
+    pub staking: Account<'info, Staking>,
+    #[account(seeds = [b"config_history", staking.key().as_ref()], bump = config_history.bump)]
+    pub config_history: Box<Account<'info, ConfigHistory>>,
+    pub beneficiary: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"member", staking.id.to_le_bytes().as_ref(), beneficiary.key().as_ref()],
+        bump = member.bump,
+    )]
+    pub member: Account<'info, Member>,
+    #[account(mut, associated_token::authority = member, associated_token::mint = staking.stake_mint)]
+    pub member_vault: Account<'info, TokenAccount>,
+    /// CHECK: stake mint for `Burn`, stake token account of factory authority for `Factory`,
+    /// staking's associated stake token account for `Redistribute`, checked in the handler
+    #[account(mut)]
+    pub penalty_destination: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+fn check_penalty_destination(ctx: &Context<InstantUnstake>, policy: PenaltyPolicy) -> Result<()> {
+    let destination = &ctx.accounts.penalty_destination;
+    let valid = match policy {
+        PenaltyPolicy::Burn => destination.key() == ctx.accounts.staking.stake_mint,
+        PenaltyPolicy::Factory => {
+            let vault = Account::<TokenAccount>::try_from(destination)?;
+            vault.owner == ctx.accounts.factory.authority
+                && vault.mint == ctx.accounts.staking.stake_mint
+        }
+        PenaltyPolicy::Redistribute => {
+            destination.key()
+                == get_associated_token_address(
+                    &ctx.accounts.staking.key(),
+                    &ctx.accounts.staking.stake_mint,
+                )
+        }
+    };
+
+    if !valid {
+        return err!(StakingError::InvalidPenaltyDestination);
+    }
+
+    Ok(())
+}
+
+fn take_penalty(ctx: &Context<InstantUnstake>, policy: PenaltyPolicy, amount: u64) -> Result<()> {
+    let signer: &[&[&[u8]]] = &[&[
+        b"member".as_ref(),
+        &ctx.accounts.staking.id.to_le_bytes(),
+        ctx.accounts.beneficiary.to_account_info().key.as_ref(),
+        &[ctx.accounts.member.bump],
+    ]];
+
+    if policy == PenaltyPolicy::Burn {
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            token::Burn {
+                mint: ctx.accounts.penalty_destination.to_account_info(),
+                from: ctx.accounts.member_vault.to_account_info(),
+                authority: ctx.accounts.member.to_account_info(),
+            },
+            signer,
+        );
+        token::burn(cpi_ctx, amount)
+    } else {
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            token::Transfer {
+                from: ctx.accounts.member_vault.to_account_info(),
+                to: ctx.accounts.penalty_destination.to_account_info(),
+                authority: ctx.accounts.member.to_account_info(),
+            },
+            signer,
+        );
+        token::transfer(cpi_ctx, amount)
+    }
+}
+
+pub fn instant_unstake(ctx: Context<InstantUnstake>, amount: u64) -> Result<()> {
+    let ts = Clock::get()?.unix_timestamp as u32;
+
+    let params = ctx
+        .accounts
+        .staking
+        .instant_unstake
+        .ok_or(StakingError::InstantUnstakeDisabled)?;
+    check_penalty_destination(&ctx, params.policy)?;
+
+    if amount == 0 {
+        return err!(StakingError::Zero);
+    }
+    if ctx.accounts.member.stake_amount < amount {
+        return err!(StakingError::InsufficientBalance);
+    }
+    if ts < ctx.accounts.member.lock_end_ts {
+        return err!(StakingError::StakeLocked);
+    }
+
+    settle_rewards(
+        ts,
+        &mut ctx.accounts.staking,
+        &ctx.accounts.config_history,
+        &mut ctx.accounts.member,
+        ctx.remaining_accounts,
+    )?;
+
+    let stake_amount = ctx.accounts.member.stake_amount - amount;
+    let lock_multiplier = ctx.accounts.member.lock_multiplier;
+    ctx.accounts.staking.set_member_stake(
+        &mut ctx.accounts.member,
+        stake_amount,
+        lock_multiplier,
+    )?;
+
+    let mut penalty = (amount as u128 * params.penalty as u128
+        / InstantUnstakeParams::PENALTY_DENOM as u128) as u64;
+
+    if params.policy == PenaltyPolicy::Redistribute {
+        // the unstaking member does not get a share of its own penalty
+        let others_stakes_sum = ctx.accounts.staking.stakes_sum - ctx.accounts.member.stake_amount;
+        if others_stakes_sum == 0 {
+            // there is no one to redistribute to
+            penalty = 0;
+        } else {
+            let penalty_per_share =
+                penalty as u128 * REWARD_PER_SHARE_PRECISION / others_stakes_sum as u128;
+            ctx.accounts.staking.penalty_per_share = ctx
+                .accounts
+                .staking
+                .penalty_per_share
+                .checked_add(penalty_per_share)
+                .ok_or(StakingError::Overflow)?;
+            ctx.accounts.member.penalty_per_share_paid = ctx.accounts.staking.penalty_per_share;
+        }
+    }
+
+    if penalty != 0 {
+        take_penalty(&ctx, params.policy, penalty)?;
+    }
+
+    let available_amount = ctx
+        .accounts
+        .member
+        .available_amount
+        .checked_add(amount - penalty)
+        .ok_or(StakingError::Overflow)?;
+    ctx.accounts
+        .staking
+        .set_member_available(&mut ctx.accounts.member, available_amount)?;
+
+    emit!(InstantUnstakeEvent {
+        beneficiary: ctx.accounts.beneficiary.key(),
+        amount,
+        penalty,
+        policy: params.policy,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use crate::{error::*, event::*, reward::*, state::*};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct Lock<'info> {
+    #[account(seeds = [b"factory"], bump = factory.bump)]
+    pub factory: Account<'info, Factory>,
+    #[account(
+        mut,
+        constraint = !staking.is_paused(&factory, PauseFlags::STAKE) @ StakingError::Paused,
+    )]
+    pub staking: Account<'info, Staking>,
+    #[account(seeds = [b"config_history", staking.key().as_ref()], bump = config_history.bump)]
+    pub config_history: Box<Account<'info, ConfigHistory>>,
+    pub beneficiary: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"member", staking.id.to_le_bytes().as_ref(), beneficiary.key().as_ref()],
+        bump = member.bump,
+    )]
+    pub member: Account<'info, Member>,
+}
+
+/// locks the whole stake, including tokens staked later, for `duration` from now
+pub fn lock(ctx: Context<Lock>, duration: u32) -> Result<()> {
+    let ts = Clock::get()?.unix_timestamp as u32;
+
+    let lock_params = ctx
+        .accounts
+        .staking
+        .lock
+        .ok_or(StakingError::LockDisabled)?;
+
+    let end_ts = ts
+        .checked_add(duration)
+        .ok_or(StakingError::InvalidLockDuration)?;
+    if duration == 0
+        || duration > lock_params.max_duration
+        || end_ts < ctx.accounts.member.lock_end_ts
+    {
+        return err!(StakingError::InvalidLockDuration);
+    }
+
+    settle_rewards(
+        ts,
+        &mut ctx.accounts.staking,
+        &ctx.accounts.config_history,
+        &mut ctx.accounts.member,
+        ctx.remaining_accounts,
+    )?;
+
+    let stake_amount = ctx.accounts.member.stake_amount;
+    let multiplier = lock_params.multiplier(duration);
+    ctx.accounts
+        .staking
+        .set_member_stake(&mut ctx.accounts.member, stake_amount, multiplier)?;
+    ctx.accounts.member.lock_end_ts = end_ts;
+
+    emit!(LockEvent {
+        beneficiary: ctx.accounts.beneficiary.key(),
+        end_ts,
+        multiplier,
+    });
+
+    Ok(())
+}
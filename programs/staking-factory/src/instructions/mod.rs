@@ -1,15 +1,55 @@
 pub use crate::instructions::{
-    change_config::*, claim_reward::*, create_staking::*, deposit::*, end_unstake::*,
-    initialize::*, register_member::*, stake::*, start_unstake::*, withdraw::*,
+    accept_factory_authority::*, accept_staking_authority::*, add_reward_stream::*,
+    cancel_unstake::*, change_config::*, change_factory_fee::*, change_staking_fee::*,
+    change_stream_config::*, claim_penalty_share::*, claim_reward::*, claim_stream_reward::*,
+    close_member::*, close_staking::*, compound::*, create_staking::*, deposit::*,
+    deposit_and_stake::*, emergency_unstake::*, end_unstake::*, end_unstake_and_withdraw::*,
+    expire_lock::*, extend_reward_stream::*, extend_staking::*, fund_reward_stream::*,
+    fund_rewards::*, initialize::*, instant_unstake::*, lock::*, propose_factory_authority::*,
+    propose_staking_authority::*, register_member::*, set_auto_compound::*,
+    set_emergency_unstake::*, set_factory_paused::*, set_staking_paused::*, stake::*,
+    start_unstake::*, sunset_staking::*, withdraw::*, withdraw_excess_rewards::*,
+    withdraw_excess_stream_rewards::*,
 };
 
+pub mod accept_factory_authority;
+pub mod accept_staking_authority;
+pub mod add_reward_stream;
+pub mod cancel_unstake;
 pub mod change_config;
+pub mod change_factory_fee;
+pub mod change_staking_fee;
+pub mod change_stream_config;
+pub mod claim_penalty_share;
 pub mod claim_reward;
+pub mod claim_stream_reward;
+pub mod close_member;
+pub mod close_staking;
+pub mod compound;
 pub mod create_staking;
 pub mod deposit;
+pub mod deposit_and_stake;
+pub mod emergency_unstake;
 pub mod end_unstake;
+pub mod end_unstake_and_withdraw;
+pub mod expire_lock;
+pub mod extend_reward_stream;
+pub mod extend_staking;
+pub mod fund_reward_stream;
+pub mod fund_rewards;
 pub mod initialize;
+pub mod instant_unstake;
+pub mod lock;
+pub mod propose_factory_authority;
+pub mod propose_staking_authority;
 pub mod register_member;
+pub mod set_auto_compound;
+pub mod set_emergency_unstake;
+pub mod set_factory_paused;
+pub mod set_staking_paused;
 pub mod stake;
 pub mod start_unstake;
+pub mod sunset_staking;
 pub mod withdraw;
+pub mod withdraw_excess_rewards;
+pub mod withdraw_excess_stream_rewards;
@@ -0,0 +1,24 @@
+use crate::{event::*, state::*};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ProposeFactoryAuthority<'info> {
+    #[account(mut, seeds = [b"factory"], bump = factory.bump, has_one = authority)]
+    pub factory: Account<'info, Factory>,
+    pub authority: Signer<'info>,
+}
+
+/// `None` cancels a pending transfer
+pub fn propose_factory_authority(
+    ctx: Context<ProposeFactoryAuthority>,
+    pending_authority: Option<Pubkey>,
+) -> Result<()> {
+    ctx.accounts.factory.pending_authority = pending_authority;
+
+    emit!(ProposeFactoryAuthorityEvent {
+        authority: ctx.accounts.authority.key(),
+        pending_authority,
+    });
+
+    Ok(())
+}
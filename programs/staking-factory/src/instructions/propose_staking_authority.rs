@@ -0,0 +1,25 @@
+use crate::{event::*, state::*};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ProposeStakingAuthority<'info> {
+    #[account(mut, has_one = authority)]
+    pub staking: Account<'info, Staking>,
+    pub authority: Signer<'info>,
+}
+
+/// `None` cancels a pending transfer
+pub fn propose_staking_authority(
+    ctx: Context<ProposeStakingAuthority>,
+    pending_authority: Option<Pubkey>,
+) -> Result<()> {
+    ctx.accounts.staking.pending_authority = pending_authority;
+
+    emit!(ProposeStakingAuthorityEvent {
+        id: ctx.accounts.staking.id,
+        authority: ctx.accounts.authority.key(),
+        pending_authority,
+    });
+
+    Ok(())
+}
@@ -1,8 +1,9 @@
-use crate::{event::*, state::*};
+use crate::{error::*, event::*, state::*};
 use anchor_lang::prelude::*;
 
 #[derive(Accounts)]
 pub struct RegisterMember<'info> {
+    #[account(mut, constraint = !staking.closing @ StakingError::StakingClosing)]
     pub staking: Account<'info, Staking>,
     #[account(mut)]
     pub beneficiary: Signer<'info>,
@@ -19,6 +20,9 @@ pub struct RegisterMember<'info> {
 
 pub fn register_member(ctx: Context<RegisterMember>) -> Result<()> {
     ctx.accounts.member.bump = *ctx.bumps.get("member").unwrap();
+    ctx.accounts.member.lock_multiplier = LockParams::MULTIPLIER_DENOM as u32;
+
+    ctx.accounts.staking.members_count += 1;
 
     emit!(RegisterMemberEvent {
         beneficiary: ctx.accounts.beneficiary.key()
@@ -0,0 +1,25 @@
+use crate::{event::*, state::*};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetAutoCompound<'info> {
+    pub staking: Account<'info, Staking>,
+    pub beneficiary: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"member", staking.id.to_le_bytes().as_ref(), beneficiary.key().as_ref()],
+        bump = member.bump,
+    )]
+    pub member: Account<'info, Member>,
+}
+
+pub fn set_auto_compound(ctx: Context<SetAutoCompound>, auto_compound: bool) -> Result<()> {
+    ctx.accounts.member.auto_compound = auto_compound;
+
+    emit!(SetAutoCompoundEvent {
+        beneficiary: ctx.accounts.beneficiary.key(),
+        auto_compound,
+    });
+
+    Ok(())
+}
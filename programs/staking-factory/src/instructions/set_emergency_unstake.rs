@@ -0,0 +1,26 @@
+use crate::{error::*, event::*, state::*};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetEmergencyUnstake<'info> {
+    #[account(seeds = [b"factory"], bump = factory.bump)]
+    pub factory: Account<'info, Factory>,
+    #[account(mut)]
+    pub staking: Account<'info, Staking>,
+    #[account(
+        constraint = authority.key() == staking.authority
+            || authority.key() == factory.authority @ StakingError::NotAuthority,
+    )]
+    pub authority: Signer<'info>,
+}
+
+pub fn set_emergency_unstake(ctx: Context<SetEmergencyUnstake>, enabled: bool) -> Result<()> {
+    ctx.accounts.staking.emergency_unstake = enabled;
+
+    emit!(SetEmergencyUnstakeEvent {
+        id: ctx.accounts.staking.id,
+        enabled,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,20 @@
+use crate::{event::*, state::*};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetFactoryPaused<'info> {
+    #[account(mut, seeds = [b"factory"], bump = factory.bump, has_one = authority)]
+    pub factory: Account<'info, Factory>,
+    pub authority: Signer<'info>,
+}
+
+/// `paused` is a combination of `PauseFlags`, zero unpauses everything
+pub fn set_factory_paused(ctx: Context<SetFactoryPaused>, paused: u8) -> Result<()> {
+    PauseFlags::validate(paused)?;
+
+    ctx.accounts.factory.paused = paused;
+
+    emit!(SetFactoryPausedEvent { paused });
+
+    Ok(())
+}
@@ -0,0 +1,29 @@
+use crate::{error::*, event::*, state::*};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetStakingPaused<'info> {
+    #[account(seeds = [b"factory"], bump = factory.bump)]
+    pub factory: Account<'info, Factory>,
+    #[account(mut)]
+    pub staking: Account<'info, Staking>,
+    #[account(
+        constraint = authority.key() == staking.authority
+            || authority.key() == factory.authority @ StakingError::NotAuthority,
+    )]
+    pub authority: Signer<'info>,
+}
+
+/// `paused` is a combination of `PauseFlags`, the factory ones apply regardless
+pub fn set_staking_paused(ctx: Context<SetStakingPaused>, paused: u8) -> Result<()> {
+    PauseFlags::validate(paused)?;
+
+    ctx.accounts.staking.paused = paused;
+
+    emit!(SetStakingPausedEvent {
+        id: ctx.accounts.staking.id,
+        paused,
+    });
+
+    Ok(())
+}
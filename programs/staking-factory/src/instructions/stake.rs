@@ -3,12 +3,16 @@ use anchor_lang::prelude::*;
 
 #[derive(Accounts)]
 pub struct Stake<'info> {
-    #[account(mut)]
+    #[account(seeds = [b"factory"], bump = factory.bump)]
+    pub factory: Account<'info, Factory>,
+    #[account(
+        mut,
+        constraint = !staking.closing @ StakingError::StakingClosing,
+        constraint = !staking.is_paused(&factory, PauseFlags::STAKE) @ StakingError::Paused,
+    )]
     pub staking: Account<'info, Staking>,
     #[account(seeds = [b"config_history", staking.key().as_ref()], bump = config_history.bump)]
     pub config_history: Box<Account<'info, ConfigHistory>>,
-    #[account(mut, seeds = [b"stakes_history", staking.key().as_ref()], bump = stakes_history.bump)]
-    pub stakes_history: Box<Account<'info, StakesHistory>>,
     pub beneficiary: Signer<'info>,
     #[account(
         mut,
@@ -18,30 +22,42 @@ pub struct Stake<'info> {
     pub member: Account<'info, Member>,
 }
 
-pub fn stake(ctx: Context<Stake>, amount: u64) -> Result<()> {
+/// shared with `deposit_and_stake`
+pub(crate) fn stake_available<'info>(
+    staking: &mut Account<'info, Staking>,
+    config_history: &Account<'info, ConfigHistory>,
+    member: &mut Account<'info, Member>,
+    reward_streams: &[AccountInfo],
+    beneficiary: Pubkey,
+    amount: u64,
+) -> Result<()> {
     let ts = Clock::get()?.unix_timestamp as u32;
 
-    if ctx.accounts.member.available_amount < amount {
+    if member.available_amount < amount {
         return err!(StakingError::InsufficientBalance);
     }
 
-    let rewards = calculate_rewards(
-        ts,
-        &ctx.accounts.staking,
-        &ctx.accounts.config_history,
-        &mut ctx.accounts.member,
-        &mut ctx.accounts.stakes_history,
-    )?;
-    ctx.accounts.member.rewards_amount += rewards;
+    settle_rewards(ts, staking, config_history, member, reward_streams)?;
 
-    ctx.accounts.member.available_amount -= amount;
-    ctx.accounts.member.stake_amount += amount;
-    ctx.accounts.staking.stakes_sum += amount;
+    staking.add_member_stake(member, amount, ts)?;
+    let available_amount = member.available_amount - amount;
+    staking.set_member_available(member, available_amount)?;
 
     emit!(StakeEvent {
-        beneficiary: ctx.accounts.beneficiary.key(),
+        beneficiary,
         amount,
     });
 
     Ok(())
 }
+
+pub fn stake(ctx: Context<Stake>, amount: u64) -> Result<()> {
+    stake_available(
+        &mut ctx.accounts.staking,
+        &ctx.accounts.config_history,
+        &mut ctx.accounts.member,
+        ctx.remaining_accounts,
+        ctx.accounts.beneficiary.key(),
+        amount,
+    )
+}
@@ -3,17 +3,20 @@ use anchor_lang::prelude::*;
 
 #[derive(Accounts)]
 pub struct StartUnstake<'info> {
+    #[account(seeds = [b"factory"], bump = factory.bump)]
+    pub factory: Account<'info, Factory>,
+    #[account(
+        mut,
+        constraint = !staking.is_paused(&factory, PauseFlags::UNSTAKE) @ StakingError::Paused,
+    )]
     pub staking: Account<'info, Staking>,
     #[account(seeds = [b"config_history", staking.key().as_ref()], bump = config_history.bump)]
     pub config_history: Box<Account<'info, ConfigHistory>>,
-    #[account(mut, seeds = [b"stakes_history", staking.key().as_ref()], bump = stakes_history.bump)]
-    pub stakes_history: Box<Account<'info, StakesHistory>>,
     pub beneficiary: Signer<'info>,
     #[account(
         mut,
         seeds = [b"member", staking.id.to_le_bytes().as_ref(), beneficiary.key().as_ref()],
         bump = member.bump,
-        constraint = !member.pending_unstake_active @ StakingError::UnstakeActive,
     )]
     pub member: Account<'info, Member>,
 }
@@ -21,29 +24,51 @@ pub struct StartUnstake<'info> {
 pub fn start_unstake(ctx: Context<StartUnstake>, amount: u64) -> Result<()> {
     let ts = Clock::get()?.unix_timestamp as u32;
 
+    if amount == 0 {
+        return err!(StakingError::Zero);
+    }
+
     if ctx.accounts.member.stake_amount < amount {
         return err!(StakingError::InsufficientBalance);
     }
+    if ts < ctx.accounts.member.lock_end_ts {
+        return err!(StakingError::StakeLocked);
+    }
+
+    let end_ts = ts + ctx.accounts.staking.unstake_timelock;
+    let pending_unstake = ctx
+        .accounts
+        .member
+        .pending_unstakes
+        .iter_mut()
+        .find(|pending_unstake| pending_unstake.amount == 0)
+        .ok_or(StakingError::TooManyUnstakes)?;
+    *pending_unstake = PendingUnstake { amount, end_ts };
 
-    let rewards = calculate_rewards(
+    settle_rewards(
         ts,
-        &ctx.accounts.staking,
+        &mut ctx.accounts.staking,
         &ctx.accounts.config_history,
         &mut ctx.accounts.member,
-        &mut ctx.accounts.stakes_history,
+        ctx.remaining_accounts,
     )?;
-    ctx.accounts.member.rewards_amount += rewards;
-
-    ctx.accounts.member.pending_unstake_active = true;
-    ctx.accounts.member.pending_unstake_end_ts = ts + ctx.accounts.staking.unstake_timelock;
 
-    ctx.accounts.member.stake_amount -= amount;
-    ctx.accounts.staking.stakes_sum -= amount;
-    ctx.accounts.member.pending_amount += amount;
+    let stake_amount = ctx.accounts.member.stake_amount - amount;
+    let lock_multiplier = ctx.accounts.member.lock_multiplier;
+    ctx.accounts.staking.set_member_stake(
+        &mut ctx.accounts.member,
+        stake_amount,
+        lock_multiplier,
+    )?;
+    let pending_amount = ctx.accounts.member.pending_amount + amount;
+    ctx.accounts
+        .staking
+        .set_member_pending(&mut ctx.accounts.member, pending_amount)?;
 
     emit!(StartUnstakeEvent {
         beneficiary: ctx.accounts.beneficiary.key(),
         amount,
+        end_ts,
     });
 
     Ok(())
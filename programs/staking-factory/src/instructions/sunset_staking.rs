@@ -0,0 +1,62 @@
+use crate::{error::*, event::*, reward::*, state::*};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SunsetStaking<'info> {
+    #[account(
+        mut,
+        has_one = authority,
+        constraint = !staking.closing @ StakingError::StakingClosing,
+    )]
+    pub staking: Account<'info, Staking>,
+    #[account(mut, seeds = [b"config_history", staking.key().as_ref()], bump = config_history.bump)]
+    pub config_history: Box<Account<'info, ConfigHistory>>,
+    pub authority: Signer<'info>,
+}
+
+/// stops accepting stakes and ends rewards at `end_ts` if it is given,
+/// the staking can be closed with `close_staking` once all members are closed.
+/// `(reward stream, config history)` of all reward streams in index order are passed
+/// in remaining accounts, their rewards end at `end_ts` as well
+pub fn sunset_staking<'info>(
+    ctx: Context<'_, '_, '_, 'info, SunsetStaking<'info>>,
+    end_ts: Option<u32>,
+) -> Result<()> {
+    let ts = Clock::get()?.unix_timestamp as u32;
+
+    // rewards already accrued cannot be taken back
+    let end_ts = end_ts.map(|end_ts| end_ts.max(ts));
+    if let Some(end_ts) = end_ts {
+        if end_ts > ctx.accounts.config_history.end_ts {
+            return err!(StakingError::InvalidEndTs);
+        }
+        ctx.accounts.config_history.end_ts = end_ts;
+    }
+
+    let mut accounts = ctx.remaining_accounts;
+    for index in 0..ctx.accounts.staking.reward_streams_count {
+        let (reward_stream, mut config_history, rest) =
+            load_stream_history(&ctx.accounts.staking, accounts)?;
+        if reward_stream.index != index {
+            return err!(StakingError::InvalidRewardStreams);
+        }
+        accounts = rest;
+
+        if let Some(end_ts) = end_ts {
+            config_history.end_ts = config_history.end_ts.min(end_ts);
+            config_history.exit(&crate::ID)?;
+        }
+    }
+    if !accounts.is_empty() {
+        return err!(StakingError::InvalidRewardStreams);
+    }
+
+    ctx.accounts.staking.closing = true;
+
+    emit!(SunsetStakingEvent {
+        id: ctx.accounts.staking.id,
+        end_ts: ctx.accounts.config_history.end_ts,
+    });
+
+    Ok(())
+}
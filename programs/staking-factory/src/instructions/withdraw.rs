@@ -1,9 +1,9 @@
-use crate::{error::*, event::*, state::*};
+use crate::{error::*, event::*, state::*, token::*};
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
 
 #[derive(Accounts)]
 pub struct Withdraw<'info> {
+    #[account(mut)]
     pub staking: Account<'info, Staking>,
     pub beneficiary: Signer<'info>,
     #[account(
@@ -12,46 +12,74 @@ pub struct Withdraw<'info> {
         bump = member.bump,
     )]
     pub member: Account<'info, Member>,
-    #[account(mut, associated_token::authority = member, associated_token::mint = staking.stake_mint)]
-    pub member_vault: Account<'info, TokenAccount>,
+    /// CHECK: associated stake token account of the member, checked in the handler
+    #[account(mut)]
+    pub member_vault: UncheckedAccount<'info>,
+    /// CHECK: stake token account, checked by the token program
     #[account(mut)]
-    pub to: Account<'info, TokenAccount>,
-    pub token_program: Program<'info, Token>,
+    pub to: UncheckedAccount<'info>,
+    /// CHECK: the stake mint
+    #[account(address = staking.stake_mint)]
+    pub stake_mint: UncheckedAccount<'info>,
+    /// CHECK: token program or Token-2022 of the stake mint, checked in the handler
+    pub token_program: UncheckedAccount<'info>,
 }
 
-fn transfer_from_member_vault(ctx: &Context<Withdraw>, amount: u64) -> Result<()> {
+/// shared with `end_unstake_and_withdraw`, transfer fees are withheld from
+/// the amount `to` receives
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn withdraw_from_member_vault<'info>(
+    token_program: &AccountInfo<'info>,
+    staking: &mut Account<'info, Staking>,
+    beneficiary: &Signer<'info>,
+    member: &mut Account<'info, Member>,
+    member_vault: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    stake_mint: &AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    if member.available_amount < amount {
+        return err!(StakingError::InsufficientBalance);
+    }
+    load_associated_token_account(member_vault, token_program, stake_mint.key, &member.key())?;
+
     let signer: &[&[&[u8]]] = &[&[
         b"member".as_ref(),
-        &ctx.accounts.staking.id.to_le_bytes(),
-        ctx.accounts.beneficiary.to_account_info().key.as_ref(),
-        &[ctx.accounts.member.bump],
+        &staking.id.to_le_bytes(),
+        beneficiary.to_account_info().key.as_ref(),
+        &[member.bump],
     ]];
-    let cpi_accounts = Transfer {
-        from: ctx.accounts.member_vault.to_account_info(),
-        to: ctx.accounts.to.to_account_info(),
-        authority: ctx.accounts.member.to_account_info(),
-    };
-    let cpi_ctx = CpiContext::new_with_signer(
-        ctx.accounts.token_program.to_account_info(),
-        cpi_accounts,
+    transfer_checked(
+        token_program,
+        member_vault,
+        stake_mint,
+        to,
+        &member.to_account_info(),
         signer,
-    );
-    token::transfer(cpi_ctx, amount)
-}
-
-pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
-    if ctx.accounts.member.available_amount < amount {
-        return err!(StakingError::InsufficientBalance);
-    }
-
-    transfer_from_member_vault(&ctx, amount)?;
+        amount,
+        staking.stake_decimals,
+    )?;
 
-    ctx.accounts.member.available_amount -= amount;
+    let available_amount = member.available_amount - amount;
+    staking.set_member_available(member, available_amount)?;
 
     emit!(WithdrawEvent {
-        beneficiary: ctx.accounts.beneficiary.key(),
+        beneficiary: beneficiary.key(),
         amount,
     });
 
     Ok(())
 }
+
+pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+    withdraw_from_member_vault(
+        &ctx.accounts.token_program,
+        &mut ctx.accounts.staking,
+        &ctx.accounts.beneficiary,
+        &mut ctx.accounts.member,
+        &ctx.accounts.member_vault,
+        &ctx.accounts.to,
+        &ctx.accounts.stake_mint,
+        amount,
+    )
+}
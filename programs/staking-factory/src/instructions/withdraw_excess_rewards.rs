@@ -0,0 +1,67 @@
+use crate::{error::*, event::*, history::*, reward::*, state::*};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+#[derive(Accounts)]
+pub struct WithdrawExcessRewards<'info> {
+    #[account(mut, has_one = authority)]
+    pub staking: Account<'info, Staking>,
+    #[account(seeds = [b"config_history", staking.key().as_ref()], bump = config_history.bump)]
+    pub config_history: Box<Account<'info, ConfigHistory>>,
+    #[account(mut, associated_token::authority = staking, associated_token::mint = staking.reward_mint)]
+    pub staking_vault: Account<'info, TokenAccount>,
+    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub to: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+fn transfer_from_staking_vault(ctx: &Context<WithdrawExcessRewards>, amount: u64) -> Result<()> {
+    let signer: &[&[&[u8]]] = &[&[
+        b"staking".as_ref(),
+        &ctx.accounts.staking.id.to_le_bytes(),
+        &[ctx.accounts.staking.bump],
+    ]];
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.staking_vault.to_account_info(),
+            to: ctx.accounts.to.to_account_info(),
+            authority: ctx.accounts.staking.to_account_info(),
+        },
+        signer,
+    );
+    token::transfer(cpi_ctx, amount)
+}
+
+/// config history pages are passed in remaining accounts
+pub fn withdraw_excess_rewards(ctx: Context<WithdrawExcessRewards>, amount: u64) -> Result<()> {
+    let ts = Clock::get()?.unix_timestamp as u32;
+
+    let (configs, _) = load_configs(&ctx.accounts.config_history, ctx.remaining_accounts)?;
+    update_staking_rewards(ts, &mut ctx.accounts.staking, &configs)?;
+
+    let staking = &ctx.accounts.staking;
+    let reserved = staking
+        .rewards_liability
+        .checked_add(promised_rewards(ts, &configs)?)
+        .ok_or(StakingError::Overflow)?;
+    let excess = (staking.total_funded - staking.total_claimed).saturating_sub(reserved);
+    if excess < amount {
+        return err!(StakingError::ReservedRewards);
+    }
+
+    transfer_from_staking_vault(&ctx, amount)?;
+
+    ctx.accounts.staking.total_funded -= amount;
+
+    emit!(WithdrawExcessRewardsEvent {
+        id: ctx.accounts.staking.id,
+        amount,
+        total_funded: ctx.accounts.staking.total_funded,
+        total_claimed: ctx.accounts.staking.total_claimed,
+        rewards_liability: ctx.accounts.staking.rewards_liability,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,86 @@
+use crate::{error::*, event::*, history::*, reward::*, state::*};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+#[derive(Accounts)]
+pub struct WithdrawExcessStreamRewards<'info> {
+    #[account(has_one = authority)]
+    pub staking: Account<'info, Staking>,
+    #[account(
+        mut,
+        seeds = [b"reward_stream", staking.key().as_ref(), reward_stream.index.to_le_bytes().as_ref()],
+        bump = reward_stream.bump,
+    )]
+    pub reward_stream: Account<'info, RewardStream>,
+    #[account(seeds = [b"config_history", reward_stream.key().as_ref()], bump = config_history.bump)]
+    pub config_history: Box<Account<'info, ConfigHistory>>,
+    #[account(mut, associated_token::authority = staking, associated_token::mint = reward_stream.reward_mint)]
+    pub stream_vault: Account<'info, TokenAccount>,
+    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub to: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+fn transfer_from_stream_vault(
+    ctx: &Context<WithdrawExcessStreamRewards>,
+    amount: u64,
+) -> Result<()> {
+    let signer: &[&[&[u8]]] = &[&[
+        b"staking".as_ref(),
+        &ctx.accounts.staking.id.to_le_bytes(),
+        &[ctx.accounts.staking.bump],
+    ]];
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.stream_vault.to_account_info(),
+            to: ctx.accounts.to.to_account_info(),
+            authority: ctx.accounts.staking.to_account_info(),
+        },
+        signer,
+    );
+    token::transfer(cpi_ctx, amount)
+}
+
+/// config history pages of the stream are passed in remaining accounts
+pub fn withdraw_excess_stream_rewards(
+    ctx: Context<WithdrawExcessStreamRewards>,
+    amount: u64,
+) -> Result<()> {
+    let ts = Clock::get()?.unix_timestamp as u32;
+
+    let (configs, _) = load_configs(&ctx.accounts.config_history, ctx.remaining_accounts)?;
+    update_stream_rewards(
+        ts,
+        &ctx.accounts.staking,
+        &mut ctx.accounts.reward_stream,
+        &configs,
+    )?;
+
+    let reward_stream = &ctx.accounts.reward_stream;
+    let reserved = reward_stream
+        .rewards_liability
+        .checked_add(promised_rewards(ts, &configs)?)
+        .ok_or(StakingError::Overflow)?;
+    let excess =
+        (reward_stream.total_funded - reward_stream.total_claimed).saturating_sub(reserved);
+    if excess < amount {
+        return err!(StakingError::ReservedRewards);
+    }
+
+    transfer_from_stream_vault(&ctx, amount)?;
+
+    ctx.accounts.reward_stream.total_funded -= amount;
+
+    emit!(WithdrawExcessStreamRewardsEvent {
+        id: ctx.accounts.staking.id,
+        index: ctx.accounts.reward_stream.index,
+        amount,
+        total_funded: ctx.accounts.reward_stream.total_funded,
+        total_claimed: ctx.accounts.reward_stream.total_claimed,
+        rewards_liability: ctx.accounts.reward_stream.rewards_liability,
+    });
+
+    Ok(())
+}
@@ -1,11 +1,13 @@
-use crate::{instructions::*, reward::*};
+use crate::{instructions::*, reward::*, state::*};
 use anchor_lang::prelude::*;
 
 pub mod error;
 pub mod event;
+pub mod history;
 pub mod instructions;
 pub mod reward;
 pub mod state;
+pub mod token;
 
 declare_id!("74Gn5o8MXGWuNgApSz7kkfcdWHGpVAcrgs41ZfW1bHbK");
 
@@ -13,31 +15,102 @@ declare_id!("74Gn5o8MXGWuNgApSz7kkfcdWHGpVAcrgs41ZfW1bHbK");
 pub mod staking_factory {
     use super::*;
 
-    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
-        instructions::initialize(ctx)
+    pub fn initialize(ctx: Context<Initialize>, fee: u16) -> Result<()> {
+        instructions::initialize(ctx, fee)
     }
 
-    pub fn create_staking(
-        ctx: Context<CreateStaking>,
-        stake_mint: Pubkey,
-        reward_mint: Pubkey,
-        unstake_timelock: u32,
+    pub fn change_factory_fee(ctx: Context<ChangeFactoryFee>, fee: u16) -> Result<()> {
+        instructions::change_factory_fee(ctx, fee)
+    }
+
+    pub fn change_staking_fee(
+        ctx: Context<ChangeStakingFee>,
+        fee_override: Option<u16>,
+    ) -> Result<()> {
+        instructions::change_staking_fee(ctx, fee_override)
+    }
+
+    pub fn propose_factory_authority(
+        ctx: Context<ProposeFactoryAuthority>,
+        pending_authority: Option<Pubkey>,
+    ) -> Result<()> {
+        instructions::propose_factory_authority(ctx, pending_authority)
+    }
+
+    pub fn accept_factory_authority(ctx: Context<AcceptFactoryAuthority>) -> Result<()> {
+        instructions::accept_factory_authority(ctx)
+    }
+
+    pub fn create_staking<'info>(
+        ctx: Context<'_, '_, '_, 'info, CreateStaking<'info>>,
         reward_params: RewardParams,
+        settings: PoolSettings,
+        start_ts: Option<u32>,
+        end_ts: Option<u32>,
     ) -> Result<()> {
-        instructions::create_staking(
-            ctx,
-            stake_mint,
-            reward_mint,
-            unstake_timelock,
-            reward_params,
-        )
+        instructions::create_staking(ctx, reward_params, settings, start_ts, end_ts)
+    }
+
+    pub fn extend_staking(ctx: Context<ExtendStaking>, end_ts: u32) -> Result<()> {
+        instructions::extend_staking(ctx, end_ts)
+    }
+
+    pub fn propose_staking_authority(
+        ctx: Context<ProposeStakingAuthority>,
+        pending_authority: Option<Pubkey>,
+    ) -> Result<()> {
+        instructions::propose_staking_authority(ctx, pending_authority)
+    }
+
+    pub fn accept_staking_authority(ctx: Context<AcceptStakingAuthority>) -> Result<()> {
+        instructions::accept_staking_authority(ctx)
     }
 
     pub fn change_config(
         ctx: Context<ChangeConfig>,
         new_reward_params: Option<RewardParams>,
+        new_settings: Option<PoolSettings>,
+    ) -> Result<()> {
+        instructions::change_config(ctx, new_reward_params, new_settings)
+    }
+
+    pub fn add_reward_stream(
+        ctx: Context<AddRewardStream>,
+        reward_params: RewardParams,
+        start_ts: Option<u32>,
+        end_ts: Option<u32>,
+    ) -> Result<()> {
+        instructions::add_reward_stream(ctx, reward_params, start_ts, end_ts)
+    }
+
+    pub fn change_stream_config(
+        ctx: Context<ChangeStreamConfig>,
+        new_reward_params: RewardParams,
+    ) -> Result<()> {
+        instructions::change_stream_config(ctx, new_reward_params)
+    }
+
+    pub fn extend_reward_stream(ctx: Context<ExtendRewardStream>, end_ts: u32) -> Result<()> {
+        instructions::extend_reward_stream(ctx, end_ts)
+    }
+
+    pub fn fund_rewards(ctx: Context<FundRewards>, amount: u64) -> Result<()> {
+        instructions::fund_rewards(ctx, amount)
+    }
+
+    pub fn fund_reward_stream(ctx: Context<FundRewardStream>, amount: u64) -> Result<()> {
+        instructions::fund_reward_stream(ctx, amount)
+    }
+
+    pub fn withdraw_excess_rewards(ctx: Context<WithdrawExcessRewards>, amount: u64) -> Result<()> {
+        instructions::withdraw_excess_rewards(ctx, amount)
+    }
+
+    pub fn withdraw_excess_stream_rewards(
+        ctx: Context<WithdrawExcessStreamRewards>,
+        amount: u64,
     ) -> Result<()> {
-        instructions::change_config(ctx, new_reward_params)
+        instructions::withdraw_excess_stream_rewards(ctx, amount)
     }
 
     pub fn register_member(ctx: Context<RegisterMember>) -> Result<()> {
@@ -52,14 +125,50 @@ pub mod staking_factory {
         instructions::stake(ctx, amount)
     }
 
+    pub fn deposit_and_stake(ctx: Context<DepositAndStake>, amount: u64) -> Result<()> {
+        instructions::deposit_and_stake(ctx, amount)
+    }
+
     pub fn claim_reward(ctx: Context<ClaimReward>) -> Result<()> {
         instructions::claim_reward(ctx)
     }
 
+    pub fn claim_stream_reward(ctx: Context<ClaimStreamReward>) -> Result<()> {
+        instructions::claim_stream_reward(ctx)
+    }
+
+    pub fn compound(ctx: Context<Compound>) -> Result<()> {
+        instructions::compound(ctx)
+    }
+
+    pub fn set_auto_compound(ctx: Context<SetAutoCompound>, auto_compound: bool) -> Result<()> {
+        instructions::set_auto_compound(ctx, auto_compound)
+    }
+
+    pub fn lock(ctx: Context<Lock>, duration: u32) -> Result<()> {
+        instructions::lock(ctx, duration)
+    }
+
+    pub fn expire_lock(ctx: Context<ExpireLock>) -> Result<()> {
+        instructions::expire_lock(ctx)
+    }
+
     pub fn start_unstake(ctx: Context<StartUnstake>, amount: u64) -> Result<()> {
         instructions::start_unstake(ctx, amount)
     }
 
+    pub fn cancel_unstake(ctx: Context<CancelUnstake>) -> Result<()> {
+        instructions::cancel_unstake(ctx)
+    }
+
+    pub fn instant_unstake(ctx: Context<InstantUnstake>, amount: u64) -> Result<()> {
+        instructions::instant_unstake(ctx, amount)
+    }
+
+    pub fn claim_penalty_share(ctx: Context<ClaimPenaltyShare>) -> Result<()> {
+        instructions::claim_penalty_share(ctx)
+    }
+
     pub fn end_unstake(ctx: Context<EndUnstake>) -> Result<()> {
         instructions::end_unstake(ctx)
     }
@@ -67,4 +176,41 @@ pub mod staking_factory {
     pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
         instructions::withdraw(ctx, amount)
     }
+
+    pub fn end_unstake_and_withdraw(ctx: Context<EndUnstakeAndWithdraw>) -> Result<()> {
+        instructions::end_unstake_and_withdraw(ctx)
+    }
+
+    pub fn close_member(ctx: Context<CloseMember>) -> Result<()> {
+        instructions::close_member(ctx)
+    }
+
+    pub fn sunset_staking<'info>(
+        ctx: Context<'_, '_, '_, 'info, SunsetStaking<'info>>,
+        end_ts: Option<u32>,
+    ) -> Result<()> {
+        instructions::sunset_staking(ctx, end_ts)
+    }
+
+    pub fn close_staking<'info>(
+        ctx: Context<'_, '_, '_, 'info, CloseStaking<'info>>,
+    ) -> Result<()> {
+        instructions::close_staking(ctx)
+    }
+
+    pub fn set_factory_paused(ctx: Context<SetFactoryPaused>, paused: u8) -> Result<()> {
+        instructions::set_factory_paused(ctx, paused)
+    }
+
+    pub fn set_staking_paused(ctx: Context<SetStakingPaused>, paused: u8) -> Result<()> {
+        instructions::set_staking_paused(ctx, paused)
+    }
+
+    pub fn set_emergency_unstake(ctx: Context<SetEmergencyUnstake>, enabled: bool) -> Result<()> {
+        instructions::set_emergency_unstake(ctx, enabled)
+    }
+
+    pub fn emergency_unstake(ctx: Context<EmergencyUnstake>) -> Result<()> {
+        instructions::emergency_unstake(ctx)
+    }
 }
@@ -1,5 +1,22 @@
-use crate::{error::*, state::*};
+use crate::{error::*, history::*, state::*};
 use anchor_lang::prelude::*;
+use std::convert::TryFrom;
+
+pub const REWARD_PER_SHARE_PRECISION: u128 = 1_000_000_000_000;
+pub const DECAY_PRECISION: u128 = 1_000_000_000_000_000_000;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, Debug, Default)]
+pub struct FixedTier {
+    pub required_amount: u64,
+    pub reward_amount: u64,
+}
+impl FixedTier {
+    pub const LEN: usize = 8 + 8;
+
+    fn is_empty(&self) -> bool {
+        self.required_amount == 0 && self.reward_amount == 0
+    }
+}
 
 #[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, Debug)]
 pub enum RewardParams {
@@ -16,6 +33,21 @@ pub enum RewardParams {
         required_period: u32,
         reward_amount: u64,
     },
+    /// `Proportional` with the amount multiplied by `decay_num / decay_denom`
+    /// every `decay_periods` reward periods, e.g. halving with 1 / 2
+    DecayingProportional {
+        initial_amount: u64,
+        reward_period: u32,
+        decay_periods: u32,
+        decay_num: u16,
+        decay_denom: u16,
+    },
+    /// `Fixed` paying by the highest tier reached, tiers are increasing
+    /// in both amounts and unused ones at the end are empty
+    TieredFixed {
+        required_period: u32,
+        tiers: [FixedTier; RewardParams::MAX_TIERS],
+    },
 }
 impl Default for RewardParams {
     fn default() -> Self {
@@ -27,7 +59,9 @@ impl Default for RewardParams {
     }
 }
 impl RewardParams {
-    pub const LEN: usize = 1 + 8 + 4 + 8;
+    pub const LEN: usize = 1 + 4 + FixedTier::LEN * RewardParams::MAX_TIERS;
+
+    pub const MAX_TIERS: usize = 4;
 
     pub fn validate_fields(&self) -> Result<()> {
         match self {
@@ -35,24 +69,167 @@ impl RewardParams {
             Self::Proportional {
                 reward_period: 0, ..
             } => err!(StakingError::Zero),
+            Self::DecayingProportional {
+                reward_period: 0, ..
+            }
+            | Self::DecayingProportional {
+                decay_periods: 0, ..
+            }
+            | Self::DecayingProportional { decay_denom: 0, .. } => err!(StakingError::Zero),
+            Self::DecayingProportional {
+                decay_num,
+                decay_denom,
+                ..
+            } if decay_num >= decay_denom => err!(StakingError::InvalidDecay),
             Self::Fixed {
+                required_amount: 0, ..
+            }
+            | Self::Fixed {
+                required_period: 0, ..
+            } => err!(StakingError::Zero),
+            Self::TieredFixed {
                 required_period: 0, ..
             } => err!(StakingError::Zero),
+            Self::TieredFixed { tiers, .. } => {
+                if tiers[0].is_empty() || tiers[0].required_amount == 0 {
+                    return err!(StakingError::InvalidTiers);
+                }
+                for window in tiers.windows(2) {
+                    let (tier, next_tier) = (window[0], window[1]);
+                    if next_tier.is_empty() {
+                        continue;
+                    }
+                    if tier.is_empty()
+                        || next_tier.required_amount <= tier.required_amount
+                        || next_tier.reward_amount <= tier.reward_amount
+                    {
+                        return err!(StakingError::InvalidTiers);
+                    }
+                }
+                Ok(())
+            }
             _ => Ok(()),
         }
     }
 
-    #[allow(clippy::too_many_arguments)]
+    /// required period and reward per it for the staked amount
+    /// if it qualifies for a `Fixed` variant
+    fn fixed_reward(&self, staked_amount: u64) -> Option<(u32, u64)> {
+        match *self {
+            Self::Fixed {
+                required_amount,
+                required_period,
+                reward_amount,
+            } if staked_amount >= required_amount => Some((required_period, reward_amount)),
+            Self::TieredFixed {
+                required_period,
+                tiers,
+            } => tiers
+                .iter()
+                .rev()
+                .find(|tier| !tier.is_empty() && staked_amount >= tier.required_amount)
+                .map(|tier| (required_period, tier.reward_amount)),
+            _ => None,
+        }
+    }
+
+    /// required period and an upper bound of the reward per it per staked token
+    /// for `Fixed` variants, scaled by `REWARD_PER_SHARE_PRECISION`.
+    /// A tier pays at most its reward per its required amount to each staked token
+    fn fixed_reserve_rate(&self) -> Option<(u32, u128)> {
+        let rate = |required_amount: u64, reward_amount: u64| {
+            ceil_div(
+                reward_amount as u128 * REWARD_PER_SHARE_PRECISION,
+                required_amount as u128,
+            )
+        };
+
+        match *self {
+            Self::Fixed {
+                required_amount,
+                required_period,
+                reward_amount,
+            } => Some((required_period, rate(required_amount, reward_amount))),
+            Self::TieredFixed {
+                required_period,
+                tiers,
+            } => tiers
+                .iter()
+                .filter(|tier| !tier.is_empty())
+                .map(|tier| rate(tier.required_amount, tier.reward_amount))
+                .max()
+                .map(|rate| (required_period, rate)),
+            _ => None,
+        }
+    }
+
+    /// length of a reward period for variants paying a total amount per period
+    pub fn reward_period(&self) -> Option<u32> {
+        match *self {
+            Self::Proportional { reward_period, .. }
+            | Self::DecayingProportional { reward_period, .. } => Some(reward_period),
+            _ => None,
+        }
+    }
+
+    /// total amount paid for reward periods `from..to` counted from the config start
+    fn emission(&self, from: u32, to: u32) -> Result<u128> {
+        match *self {
+            Self::Proportional { total_amount, .. } => {
+                Ok(total_amount as u128 * (to - from) as u128)
+            }
+            Self::DecayingProportional {
+                initial_amount,
+                decay_periods,
+                decay_num,
+                decay_denom,
+                ..
+            } => {
+                if from >= to {
+                    return Ok(0);
+                }
+                // decay spans of the first and the last reward periods
+                let (first_span, last_span) = (from / decay_periods, (to - 1) / decay_periods);
+                let span_start = |span: u32| span as u64 * decay_periods as u64;
+
+                let span_amount = |span: u32| {
+                    initial_amount as u128 * decay_factor(decay_num, decay_denom, span)
+                        / DECAY_PRECISION
+                };
+                if first_span == last_span {
+                    return Ok(span_amount(first_span) * (to - from) as u128);
+                }
+
+                let head =
+                    span_amount(first_span) * (span_start(first_span + 1) - from as u64) as u128;
+                let tail = span_amount(last_span) * (to as u64 - span_start(last_span)) as u128;
+                // whole spans in between
+                let whole = (initial_amount as u128)
+                    .checked_mul(geometric_sum(
+                        decay_num,
+                        decay_denom,
+                        first_span + 1,
+                        last_span,
+                    ))
+                    .ok_or(StakingError::Overflow)?
+                    / DECAY_PRECISION
+                    * decay_periods as u128;
+
+                head.checked_add(tail)
+                    .and_then(|res| res.checked_add(whole))
+                    .ok_or_else(|| error!(StakingError::Overflow))
+            }
+            _ => Ok(0),
+        }
+    }
+
     fn get_reward_amount(
         &self,
         staked_amount: u64,
-        current_stakes_sum: u64,
         last_reward_ts: &mut u32,
         current_ts: u32,
         config_start_ts: u32,
         config_end_ts: u32,
-        stakes_history: &mut Account<StakesHistory>,
-        offset: u8,
     ) -> Result<u64> {
         if *last_reward_ts == 0 {
             *last_reward_ts = current_ts;
@@ -78,46 +255,20 @@ impl RewardParams {
                     .ok_or(StakingError::Overflow)?
                     / denom
             }
-            Self::Proportional {
-                total_amount,
-                reward_period,
-                ..
-            } => {
-                let past_rewards_count = (start_ts - config_start_ts) / reward_period;
-                let all_rewards_count = (end_ts - config_start_ts) / reward_period;
-                let rewards_count = all_rewards_count - past_rewards_count;
-                *last_reward_ts += rewards_count * reward_period;
-
-                let mut reward_amount = 0u64;
-
-                for i in offset as u32 + past_rewards_count..offset as u32 + all_rewards_count {
-                    if (stakes_history.len as u32) <= i {
-                        // no one has checked this reward yet so its stakes_sum becomes current
-                        stakes_history.stakes_sums[i as usize] = current_stakes_sum;
-                        stakes_history.len += 1;
-                    }
-
-                    reward_amount = reward_amount
-                        .checked_add(
-                            staked_amount
-                                .checked_mul(total_amount)
-                                .ok_or(StakingError::Overflow)?
-                                .checked_div(stakes_history.stakes_sums[i as usize])
-                                .unwrap_or(0),
-                        )
-                        .ok_or(StakingError::Overflow)?;
-                }
-
-                reward_amount
+            Self::Proportional { .. } | Self::DecayingProportional { .. } => {
+                // paid from the staking's reward per share accumulator
+                *last_reward_ts = end_ts;
+                0
             }
-            Self::Fixed {
-                required_amount,
-                required_period,
-                reward_amount,
-            } => {
-                if staked_amount < required_amount {
-                    return Ok(0);
-                }
+            Self::Fixed { .. } | Self::TieredFixed { .. } => {
+                let (required_period, reward_amount) = match self.fixed_reward(staked_amount) {
+                    Some(fixed_reward) => fixed_reward,
+                    None => {
+                        // time below the first tier is not paid once it is reached
+                        *last_reward_ts = end_ts;
+                        return Ok(0);
+                    }
+                };
 
                 let rewards_count = (end_ts - start_ts) / required_period;
                 *last_reward_ts += rewards_count * required_period;
@@ -145,37 +296,591 @@ impl RewardParams {
     }
 }
 
-pub fn calculate_rewards<'info>(
+/// Accrues rewards per share for every `Proportional` reward period finished since
+/// the last update, the weighted stakes sum could not change in between so it is current.
+/// Rewards that depend only on the stakes sum are also added to the liability here,
+/// rounded up as members round their shares down. `Fixed` rewards depend on each
+/// member's stake, so an upper bound of them is reserved instead and the excess is
+/// released once members settle
+fn accrue_rewards(
     current_ts: u32,
-    staking: &Account<'info, Staking>,
-    config_history: &Account<'info, ConfigHistory>,
-    member: &mut Account<'info, Member>,
-    stakes_history: &mut Account<'info, StakesHistory>,
-) -> Result<u64> {
-    let mut res = 0u64;
-
-    for i in 0..config_history.len {
-        let offset = stakes_history.offsets[i as usize];
-        let reward_amount = {
-            (config_history.reward_params[i as usize]).get_reward_amount(
-                member.stake_amount,
-                staking.stakes_sum,
-                &mut member.last_reward_ts,
-                current_ts,
-                config_history.start_timestamps[i as usize],
-                if i + 1 == config_history.len {
-                    u32::MAX
+    weighted_stakes_sum: u64,
+    configs: &Configs,
+    reward_per_share: &mut u128,
+    reward_per_share_ts: &mut u32,
+    fixed_reserve_per_share: &mut u128,
+    rewards_liability: &mut u64,
+) -> Result<()> {
+    for i in configs.index_at(*reward_per_share_ts)?..configs.reward_params.len() {
+        let config_start_ts = configs.start_timestamps[i];
+        let config_end_ts = configs.end_ts(i);
+
+        let start_ts = config_start_ts.max(*reward_per_share_ts);
+        let end_ts = config_end_ts.min(current_ts);
+
+        if start_ts >= end_ts {
+            continue;
+        }
+
+        let accrued = match configs.reward_params[i] {
+            RewardParams::InterestRate { num, denom } => {
+                *reward_per_share_ts = end_ts;
+
+                let numerator = (weighted_stakes_sum as u128)
+                    .checked_mul((end_ts - start_ts) as u128)
+                    .ok_or(StakingError::Overflow)?
+                    .checked_mul(num as u128)
+                    .ok_or(StakingError::Overflow)?;
+                ceil_div(numerator, denom as u128)
+            }
+            reward_params @ RewardParams::Proportional { reward_period, .. }
+            | reward_params @ RewardParams::DecayingProportional { reward_period, .. } => {
+                let past_rewards_count = (start_ts - config_start_ts) / reward_period;
+                let all_rewards_count = (end_ts - config_start_ts) / reward_period;
+                *reward_per_share_ts = config_start_ts + all_rewards_count * reward_period;
+
+                if weighted_stakes_sum == 0 {
+                    0
                 } else {
-                    config_history.start_timestamps[(i + 1) as usize]
-                },
-                stakes_history,
-                offset,
-            )?
+                    let accrued = reward_params.emission(past_rewards_count, all_rewards_count)?;
+                    let reward_per_share_increase = accrued
+                        .checked_mul(REWARD_PER_SHARE_PRECISION)
+                        .ok_or(StakingError::Overflow)?
+                        / weighted_stakes_sum as u128;
+                    *reward_per_share = reward_per_share
+                        .checked_add(reward_per_share_increase)
+                        .ok_or(StakingError::Overflow)?;
+                    accrued
+                }
+            }
+            reward_params @ RewardParams::Fixed { .. }
+            | reward_params @ RewardParams::TieredFixed { .. } => {
+                *reward_per_share_ts = end_ts;
+
+                let (required_period, rate) = reward_params
+                    .fixed_reserve_rate()
+                    .ok_or(StakingError::InvalidTiers)?;
+                let reserve_per_share_increase = ceil_div(
+                    rate.checked_mul((end_ts - start_ts) as u128)
+                        .ok_or(StakingError::Overflow)?,
+                    required_period as u128,
+                );
+                *fixed_reserve_per_share = fixed_reserve_per_share
+                    .checked_add(reserve_per_share_increase)
+                    .ok_or(StakingError::Overflow)?;
+
+                ceil_div(
+                    (weighted_stakes_sum as u128)
+                        .checked_mul(reserve_per_share_increase)
+                        .ok_or(StakingError::Overflow)?,
+                    REWARD_PER_SHARE_PRECISION,
+                )
+            }
         };
-        res = res
-            .checked_add(reward_amount)
+
+        *rewards_liability = u64::try_from(accrued)
+            .ok()
+            .and_then(|accrued| rewards_liability.checked_add(accrued))
             .ok_or(StakingError::Overflow)?;
     }
 
+    Ok(())
+}
+
+/// Member's accrual state of the staking or a reward stream
+struct MemberAccrual<'a> {
+    reward_per_share_paid: &'a mut u128,
+    fixed_reserve_per_share_paid: &'a mut u128,
+    fixed_reserve: &'a mut u64,
+    last_reward_ts: &'a mut u32,
+}
+
+/// Reserve of `Fixed` rewards for the reward period in progress of the config
+/// active at `last_reward_ts`, these are paid once it is finished
+fn fixed_reserve_in_progress(
+    current_ts: u32,
+    configs: &Configs,
+    weighted_amount: u64,
+    last_reward_ts: u32,
+) -> Result<u64> {
+    let i = configs.index_at(last_reward_ts)?;
+    let (required_period, rate) = match configs.reward_params[i].fixed_reserve_rate() {
+        Some(fixed_reserve_rate) => fixed_reserve_rate,
+        None => return Ok(0),
+    };
+
+    let start_ts = configs.start_timestamps[i].max(last_reward_ts);
+    let end_ts = configs.end_ts(i).min(current_ts);
+    if start_ts >= end_ts {
+        return Ok(0);
+    }
+
+    u64::try_from(
+        (weighted_amount as u128)
+            .checked_mul(rate)
+            .and_then(|amount| amount.checked_mul((end_ts - start_ts) as u128))
+            .ok_or(StakingError::Overflow)?
+            / required_period as u128
+            / REWARD_PER_SHARE_PRECISION,
+    )
+    .map_err(|_| error!(StakingError::Overflow))
+}
+
+/// Pays the member everything accrued since its last settlement,
+/// its weighted amount could not change in between.
+/// The `Fixed` rewards reserved for the member since then are replaced in the liability
+/// by the ones paid and the reserve for the reward period in progress
+fn settle_member_rewards(
+    current_ts: u32,
+    configs: &Configs,
+    reward_per_share: u128,
+    fixed_reserve_per_share: u128,
+    rewards_liability: &mut u64,
+    weighted_amount: u64,
+    member_accrual: MemberAccrual,
+) -> Result<u64> {
+    let MemberAccrual {
+        reward_per_share_paid,
+        fixed_reserve_per_share_paid,
+        fixed_reserve,
+        last_reward_ts,
+    } = member_accrual;
+
+    let mut res = u64::try_from(
+        (weighted_amount as u128)
+            .checked_mul(reward_per_share - *reward_per_share_paid)
+            .ok_or(StakingError::Overflow)?
+            / REWARD_PER_SHARE_PRECISION,
+    )
+    .map_err(|_| StakingError::Overflow)?;
+    *reward_per_share_paid = reward_per_share;
+
+    let reserved = u64::try_from(
+        (weighted_amount as u128)
+            .checked_mul(fixed_reserve_per_share - *fixed_reserve_per_share_paid)
+            .ok_or(StakingError::Overflow)?
+            / REWARD_PER_SHARE_PRECISION,
+    )
+    .map_err(|_| StakingError::Overflow)?
+    .checked_add(*fixed_reserve)
+    .ok_or(StakingError::Overflow)?;
+    *fixed_reserve_per_share_paid = fixed_reserve_per_share;
+
+    let mut fixed_rewards: u64 = 0;
+    if *last_reward_ts == 0 {
+        *last_reward_ts = current_ts;
+    } else {
+        for i in configs.index_at(*last_reward_ts)?..configs.reward_params.len() {
+            let reward_params = configs.reward_params[i];
+            let reward_amount = reward_params.get_reward_amount(
+                weighted_amount,
+                last_reward_ts,
+                current_ts,
+                configs.start_timestamps[i],
+                configs.end_ts(i),
+            )?;
+            res = res
+                .checked_add(reward_amount)
+                .ok_or(StakingError::Overflow)?;
+
+            if let RewardParams::Fixed { .. } | RewardParams::TieredFixed { .. } = reward_params {
+                fixed_rewards += reward_amount;
+            }
+        }
+    }
+
+    *fixed_reserve =
+        fixed_reserve_in_progress(current_ts, configs, weighted_amount, *last_reward_ts)?;
+    *rewards_liability = rewards_liability
+        .checked_add(fixed_rewards)
+        .and_then(|liability| liability.checked_add(*fixed_reserve))
+        .ok_or(StakingError::Overflow)?
+        .saturating_sub(reserved);
+
+    Ok(res)
+}
+
+pub fn update_staking_rewards(
+    current_ts: u32,
+    staking: &mut Staking,
+    configs: &Configs,
+) -> Result<()> {
+    accrue_rewards(
+        current_ts,
+        staking.weighted_stakes_sum,
+        configs,
+        &mut staking.reward_per_share,
+        &mut staking.reward_per_share_ts,
+        &mut staking.fixed_reserve_per_share,
+        &mut staking.rewards_liability,
+    )
+}
+
+pub fn update_stream_rewards(
+    current_ts: u32,
+    staking: &Staking,
+    reward_stream: &mut RewardStream,
+    configs: &Configs,
+) -> Result<()> {
+    accrue_rewards(
+        current_ts,
+        staking.weighted_stakes_sum,
+        configs,
+        &mut reward_stream.reward_per_share,
+        &mut reward_stream.reward_per_share_ts,
+        &mut reward_stream.fixed_reserve_per_share,
+        &mut reward_stream.rewards_liability,
+    )
+}
+
+/// Remaining rewards of the reward period in progress
+pub fn promised_rewards(current_ts: u32, configs: &Configs) -> Result<u64> {
+    if current_ts >= configs.end_ts {
+        return Ok(0);
+    }
+
+    let i = configs.index_at(current_ts)?;
+    if current_ts < configs.start_timestamps[i] {
+        // not started yet
+        return Ok(0);
+    }
+    let reward_params = configs.reward_params[i];
+    match reward_params.reward_period() {
+        Some(reward_period) => {
+            let rewards_count = (current_ts - configs.start_timestamps[i]) / reward_period;
+            u64::try_from(reward_params.emission(rewards_count, rewards_count + 1)?)
+                .map_err(|_| error!(StakingError::Overflow))
+        }
+        None => Ok(0),
+    }
+}
+
+fn ceil_div(numerator: u128, denom: u128) -> u128 {
+    let res = numerator / denom;
+    if res * denom == numerator {
+        res
+    } else {
+        res + 1
+    }
+}
+
+/// Sum of `decay_num / decay_denom` to the powers of `from..to`, scaled by
+/// `DECAY_PRECISION`. The decay is below one, see `RewardParams::validate_fields`
+fn geometric_sum(decay_num: u16, decay_denom: u16, from: u32, to: u32) -> u128 {
+    let factor = DECAY_PRECISION * decay_num as u128 / decay_denom as u128;
+    decay_factor(decay_num, decay_denom, from).saturating_sub(decay_factor(
+        decay_num,
+        decay_denom,
+        to,
+    )) * DECAY_PRECISION
+        / (DECAY_PRECISION - factor)
+}
+
+/// `decay_num / decay_denom` to the power of `exp`, scaled by `DECAY_PRECISION`
+fn decay_factor(decay_num: u16, decay_denom: u16, mut exp: u32) -> u128 {
+    let mut base = DECAY_PRECISION * decay_num as u128 / decay_denom as u128;
+    let mut res = DECAY_PRECISION;
+    while exp != 0 {
+        if exp & 1 == 1 {
+            res = res * base / DECAY_PRECISION;
+        }
+        base = base * base / DECAY_PRECISION;
+        exp >>= 1;
+    }
+    res
+}
+
+/// Settles member's rewards of the staking and resets the lock multiplier once the lock
+/// is over, all reward streams must be settled before
+pub fn calculate_rewards(
+    current_ts: u32,
+    staking: &mut Staking,
+    configs: &Configs,
+    member: &mut Member,
+) -> Result<u64> {
+    update_staking_rewards(current_ts, staking, configs)?;
+
+    let res = settle_member_rewards(
+        current_ts,
+        configs,
+        staking.reward_per_share,
+        staking.fixed_reserve_per_share,
+        &mut staking.rewards_liability,
+        member.weighted_amount,
+        MemberAccrual {
+            reward_per_share_paid: &mut member.reward_per_share_paid,
+            fixed_reserve_per_share_paid: &mut member.fixed_reserve_per_share_paid,
+            fixed_reserve: &mut member.fixed_reserve,
+            last_reward_ts: &mut member.last_reward_ts,
+        },
+    )?;
+
+    let penalty_share = u64::try_from(
+        (member.stake_amount as u128)
+            .checked_mul(staking.penalty_per_share - member.penalty_per_share_paid)
+            .ok_or(StakingError::Overflow)?
+            / REWARD_PER_SHARE_PRECISION,
+    )
+    .map_err(|_| StakingError::Overflow)?;
+    member.penalty_share_amount = member
+        .penalty_share_amount
+        .checked_add(penalty_share)
+        .ok_or(StakingError::Overflow)?;
+    staking.penalty_shares_sum = staking
+        .penalty_shares_sum
+        .checked_add(penalty_share)
+        .ok_or(StakingError::Overflow)?;
+    member.penalty_per_share_paid = staking.penalty_per_share;
+
+    // the weighted stakes sum changes, so reward streams must be settled before
+    if member.lock_end_ts <= current_ts
+        && member.lock_multiplier as u64 != LockParams::MULTIPLIER_DENOM
+    {
+        let stake_amount = member.stake_amount;
+        staking.set_member_stake(member, stake_amount, LockParams::MULTIPLIER_DENOM as u32)?;
+    }
+
     Ok(res)
 }
+
+pub fn calculate_stream_rewards(
+    current_ts: u32,
+    staking: &Staking,
+    reward_stream: &mut RewardStream,
+    configs: &Configs,
+    member: &mut Member,
+) -> Result<u64> {
+    update_stream_rewards(current_ts, staking, reward_stream, configs)?;
+
+    let member_stream = &mut member.reward_streams[reward_stream.index as usize];
+    if member_stream.last_reward_ts == 0 {
+        if member.weighted_amount == 0 {
+            // nothing is accrued without a stake, so the stream's past configs are not needed
+            member_stream.last_reward_ts = current_ts;
+            member_stream.reward_per_share_paid = reward_stream.reward_per_share;
+            member_stream.fixed_reserve_per_share_paid = reward_stream.fixed_reserve_per_share;
+        } else {
+            // the weighted amount is unchanged since the stream was added,
+            // as every stake change settles all streams
+            member_stream.last_reward_ts = configs.start_ts;
+        }
+    }
+
+    settle_member_rewards(
+        current_ts,
+        configs,
+        reward_stream.reward_per_share,
+        reward_stream.fixed_reserve_per_share,
+        &mut reward_stream.rewards_liability,
+        member.weighted_amount,
+        MemberAccrual {
+            reward_per_share_paid: &mut member_stream.reward_per_share_paid,
+            fixed_reserve_per_share_paid: &mut member_stream.fixed_reserve_per_share_paid,
+            fixed_reserve: &mut member_stream.fixed_reserve,
+            last_reward_ts: &mut member_stream.last_reward_ts,
+        },
+    )
+}
+
+/// Loads a reward stream of the staking from `account`
+fn load_stream<'info>(
+    staking: &Account<Staking>,
+    account: &AccountInfo<'info>,
+) -> Result<Account<'info, RewardStream>> {
+    let reward_stream = Account::<RewardStream>::try_from(account)?;
+    let reward_stream_key = Pubkey::create_program_address(
+        &[
+            b"reward_stream",
+            staking.key().as_ref(),
+            &[reward_stream.index],
+            &[reward_stream.bump],
+        ],
+        &crate::ID,
+    )
+    .map_err(|_| StakingError::InvalidRewardStreams)?;
+    if reward_stream.key() != reward_stream_key {
+        return err!(StakingError::InvalidRewardStreams);
+    }
+
+    Ok(reward_stream)
+}
+
+/// Loads a reward stream of the staking and its config history from
+/// `(reward stream, config history)` leading `accounts`, returns the accounts after them
+pub fn load_stream_history<'a, 'info>(
+    staking: &Account<Staking>,
+    accounts: &'a [AccountInfo<'info>],
+) -> Result<(
+    Account<'info, RewardStream>,
+    Account<'info, ConfigHistory>,
+    &'a [AccountInfo<'info>],
+)> {
+    if accounts.len() < 2 {
+        return err!(StakingError::InvalidRewardStreams);
+    }
+    let reward_stream = load_stream(staking, &accounts[0])?;
+    let config_history = Account::<ConfigHistory>::try_from(&accounts[1])?;
+
+    let config_history_key = Pubkey::create_program_address(
+        &[
+            b"config_history",
+            reward_stream.key().as_ref(),
+            &[config_history.bump],
+        ],
+        &crate::ID,
+    )
+    .map_err(|_| StakingError::InvalidRewardStreams)?;
+    if config_history.key() != config_history_key {
+        return err!(StakingError::InvalidRewardStreams);
+    }
+
+    Ok((reward_stream, config_history, &accounts[2..]))
+}
+
+/// `(reward stream, config history, config history pages..)` leading `accounts`,
+/// returns the accounts after them
+#[allow(clippy::type_complexity)]
+pub fn load_reward_stream<'a, 'info>(
+    staking: &Account<Staking>,
+    accounts: &'a [AccountInfo<'info>],
+) -> Result<(
+    Account<'info, RewardStream>,
+    Account<'info, ConfigHistory>,
+    Configs,
+    &'a [AccountInfo<'info>],
+)> {
+    let (reward_stream, config_history, accounts) = load_stream_history(staking, accounts)?;
+    let (configs, accounts) = load_configs(&config_history, accounts)?;
+
+    Ok((reward_stream, config_history, configs, accounts))
+}
+
+/// Settles member's rewards of the staking and all its reward streams,
+/// must precede any change of the member's weighted amount.
+/// `accounts` are the staking's config history pages followed by the reward streams
+/// in index order, see `load_reward_stream`
+pub fn settle_rewards<'info, 'a>(
+    current_ts: u32,
+    staking: &mut Account<'info, Staking>,
+    config_history: &Account<'info, ConfigHistory>,
+    member: &mut Account<'info, Member>,
+    accounts: &[AccountInfo<'a>],
+) -> Result<()> {
+    let (configs, mut accounts) = load_configs(config_history, accounts)?;
+
+    for index in 0..staking.reward_streams_count as usize {
+        let (mut reward_stream, _, configs, rest) = load_reward_stream(staking, accounts)?;
+        if reward_stream.index as usize != index {
+            return err!(StakingError::InvalidRewardStreams);
+        }
+        accounts = rest;
+
+        let rewards =
+            calculate_stream_rewards(current_ts, staking, &mut reward_stream, &configs, member)?;
+        let member_stream = &mut member.reward_streams[index];
+        member_stream.rewards_amount = member_stream
+            .rewards_amount
+            .checked_add(rewards)
+            .ok_or(StakingError::Overflow)?;
+        reward_stream.unclaimed_rewards = reward_stream
+            .unclaimed_rewards
+            .checked_add(rewards)
+            .ok_or(StakingError::Overflow)?;
+
+        reward_stream.exit(&crate::ID)?;
+    }
+    if !accounts.is_empty() {
+        return err!(StakingError::InvalidRewardStreams);
+    }
+
+    let rewards = calculate_rewards(current_ts, staking, &configs, member)?;
+    member.rewards_amount = member
+        .rewards_amount
+        .checked_add(rewards)
+        .ok_or(StakingError::Overflow)?;
+    staking.unclaimed_rewards = staking
+        .unclaimed_rewards
+        .checked_add(rewards)
+        .ok_or(StakingError::Overflow)?;
+
+    Ok(())
+}
+
+/// Rewards of a member in the liability but not yet paid: the ones settled and the ones
+/// accrued to the accumulators since, `Fixed` reserves included
+fn unsettled_liability(
+    reward_per_share: u128,
+    fixed_reserve_per_share: u128,
+    weighted_amount: u64,
+    rewards_amount: u64,
+    reward_per_share_paid: u128,
+    fixed_reserve_per_share_paid: u128,
+    fixed_reserve: u64,
+) -> Result<u64> {
+    let accrued = (weighted_amount as u128)
+        .checked_mul(
+            (reward_per_share - reward_per_share_paid)
+                + (fixed_reserve_per_share - fixed_reserve_per_share_paid),
+        )
+        .ok_or(StakingError::Overflow)?
+        / REWARD_PER_SHARE_PRECISION;
+
+    u64::try_from(accrued)
+        .ok()
+        .and_then(|accrued| accrued.checked_add(rewards_amount))
+        .and_then(|liability| liability.checked_add(fixed_reserve))
+        .ok_or_else(|| error!(StakingError::Overflow))
+}
+
+/// Releases the staking's liability for a member who forfeits its rewards or leaves,
+/// calculates nothing so that it works even if calculating rewards fails
+pub fn release_member_liability(staking: &mut Staking, member: &Member) -> Result<()> {
+    let liability = unsettled_liability(
+        staking.reward_per_share,
+        staking.fixed_reserve_per_share,
+        member.weighted_amount,
+        member.rewards_amount,
+        member.reward_per_share_paid,
+        member.fixed_reserve_per_share_paid,
+        member.fixed_reserve,
+    )?;
+    staking.rewards_liability = staking.rewards_liability.saturating_sub(liability);
+    staking.unclaimed_rewards -= member.rewards_amount;
+
+    Ok(())
+}
+
+/// Same as `release_member_liability` for all reward streams of the staking,
+/// passed as `accounts` in index order
+pub fn release_member_stream_liabilities(
+    staking: &Account<Staking>,
+    member: &Member,
+    accounts: &[AccountInfo],
+) -> Result<()> {
+    if accounts.len() != staking.reward_streams_count as usize {
+        return err!(StakingError::InvalidRewardStreams);
+    }
+
+    for (index, account) in accounts.iter().enumerate() {
+        let mut reward_stream = load_stream(staking, account)?;
+        if reward_stream.index as usize != index {
+            return err!(StakingError::InvalidRewardStreams);
+        }
+
+        let member_stream = &member.reward_streams[index];
+        let liability = unsettled_liability(
+            reward_stream.reward_per_share,
+            reward_stream.fixed_reserve_per_share,
+            member.weighted_amount,
+            member_stream.rewards_amount,
+            member_stream.reward_per_share_paid,
+            member_stream.fixed_reserve_per_share_paid,
+            member_stream.fixed_reserve,
+        )?;
+        reward_stream.rewards_liability = reward_stream.rewards_liability.saturating_sub(liability);
+        reward_stream.unclaimed_rewards -= member_stream.rewards_amount;
+        reward_stream.exit(&crate::ID)?;
+    }
+
+    Ok(())
+}
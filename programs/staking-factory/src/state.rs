@@ -1,52 +1,367 @@
-use crate::reward::*;
+use crate::{error::*, reward::*};
 use anchor_lang::prelude::*;
+use std::convert::TryFrom;
 
 #[account]
 pub struct Factory {
     pub bump: u8,
     pub authority: Pubkey,
+    pub pending_authority: Option<Pubkey>,
     pub stakings_count: u16,
+    pub fee: u16,
+    /// `PauseFlags` blocked in all stakings
+    pub paused: u8,
 }
 impl Factory {
-    pub const LEN: usize = 1 + 32 + 2;
+    pub const LEN: usize = 1 + 32 + (1 + 32) + 2 + 2 + 1;
+
+    pub const FEE_DENOM: u64 = 10_000;
+    pub const MAX_FEE: u16 = 1_000;
 }
 
 #[account]
 pub struct Staking {
     pub bump: u8,
     pub authority: Pubkey,
+    pub pending_authority: Option<Pubkey>,
     pub id: u16,
     pub unstake_timelock: u32,
     pub stake_mint: Pubkey,
     pub reward_mint: Pubkey,
     pub reward_params: RewardParams,
     pub stakes_sum: u64,
+    /// accumulated `Proportional` rewards per staked token,
+    /// scaled by `REWARD_PER_SHARE_PRECISION`
+    pub reward_per_share: u128,
+    /// time up to which rewards are accrued to `reward_per_share` and `rewards_liability`
+    pub reward_per_share_ts: u32,
+    pub total_funded: u64,
+    pub total_claimed: u64,
+    /// rewards accrued to members but not yet claimed, may be rounded up
+    pub rewards_liability: u64,
+    /// overrides factory fee for this staking if set by factory authority
+    pub fee_override: Option<u16>,
+    /// allows to skip the unstake timelock for a penalty if set
+    pub instant_unstake: Option<InstantUnstakeParams>,
+    /// accumulated redistributed penalties per staked token,
+    /// scaled by `REWARD_PER_SHARE_PRECISION`
+    pub penalty_per_share: u128,
+    /// allows to lock stakes for a reward weight multiplier if set
+    pub lock: Option<LockParams>,
+    /// sum of members' `weighted_amount`, used for rewards instead of `stakes_sum`
+    pub weighted_stakes_sum: u64,
+    /// additional reward streams, indexed from 0
+    pub reward_streams_count: u8,
+    /// set by `sunset_staking`, no new members or stakes are accepted
+    pub closing: bool,
+    /// registered members not yet closed
+    pub members_count: u32,
+    /// `PauseFlags` blocked in this staking in addition to the factory ones
+    pub paused: u8,
+    /// allows members to recover their stakes with `emergency_unstake`
+    pub emergency_unstake: bool,
+    pub stake_decimals: u8,
+    pub reward_decimals: u8,
+    /// accumulated upper bound of `Fixed` rewards per staked token reserved in
+    /// `rewards_liability`, scaled by `REWARD_PER_SHARE_PRECISION`
+    pub fixed_reserve_per_share: u128,
+    /// sum of members' `available_amount`
+    pub available_sum: u64,
+    /// sum of members' `pending_amount`
+    pub pending_sum: u64,
+    /// sum of members' `penalty_share_amount`
+    pub penalty_shares_sum: u64,
+    /// sum of members' settled `rewards_amount`, exact unlike `rewards_liability`
+    pub unclaimed_rewards: u64,
 }
 impl Staking {
-    pub const LEN: usize = 1 + 32 + 2 + 4 + 32 + 32 + RewardParams::LEN + 8;
+    pub const LEN: usize = 1
+        + 32
+        + (1 + 32)
+        + 2
+        + 4
+        + 32
+        + 32
+        + RewardParams::LEN
+        + 8
+        + 16
+        + 4
+        + 8
+        + 8
+        + 8
+        + (1 + 2)
+        + (1 + InstantUnstakeParams::LEN)
+        + 16
+        + (1 + LockParams::LEN)
+        + 8
+        + 1
+        + 1
+        + 4
+        + 1
+        + 1
+        + 1
+        + 1
+        + 16
+        + 8
+        + 8
+        + 8
+        + 8;
+
+    pub const MAX_REWARD_STREAMS: usize = 4;
+
+    pub fn is_paused(&self, factory: &Factory, flags: u8) -> bool {
+        (self.paused | factory.paused) & flags != 0
+    }
+
+    /// sets member's stake and lock multiplier keeping the stakes sums in sync
+    pub fn set_member_stake(
+        &mut self,
+        member: &mut Member,
+        stake_amount: u64,
+        lock_multiplier: u32,
+    ) -> Result<()> {
+        let weighted_amount = u64::try_from(
+            stake_amount as u128 * lock_multiplier as u128 / LockParams::MULTIPLIER_DENOM as u128,
+        )
+        .map_err(|_| StakingError::Overflow)?;
+
+        self.stakes_sum = (self.stakes_sum - member.stake_amount)
+            .checked_add(stake_amount)
+            .ok_or(StakingError::Overflow)?;
+        self.weighted_stakes_sum = (self.weighted_stakes_sum - member.weighted_amount)
+            .checked_add(weighted_amount)
+            .ok_or(StakingError::Overflow)?;
+
+        member.stake_amount = stake_amount;
+        member.lock_multiplier = lock_multiplier;
+        member.weighted_amount = weighted_amount;
+
+        Ok(())
+    }
+
+    /// sets member's available amount keeping `available_sum` in sync
+    pub fn set_member_available(&mut self, member: &mut Member, amount: u64) -> Result<()> {
+        self.available_sum = (self.available_sum - member.available_amount)
+            .checked_add(amount)
+            .ok_or(StakingError::Overflow)?;
+        member.available_amount = amount;
+
+        Ok(())
+    }
+
+    /// sets member's pending amount keeping `pending_sum` in sync
+    pub fn set_member_pending(&mut self, member: &mut Member, amount: u64) -> Result<()> {
+        self.pending_sum = (self.pending_sum - member.pending_amount)
+            .checked_add(amount)
+            .ok_or(StakingError::Overflow)?;
+        member.pending_amount = amount;
+
+        Ok(())
+    }
+
+    /// adds to member's stake, the added tokens are locked along with the rest of the stake
+    /// and get the multiplier of the remaining lock duration
+    pub fn add_member_stake(
+        &mut self,
+        member: &mut Member,
+        amount: u64,
+        current_ts: u32,
+    ) -> Result<()> {
+        let added_multiplier = match self.lock {
+            Some(lock) if member.lock_end_ts > current_ts => {
+                lock.multiplier((member.lock_end_ts - current_ts).min(lock.max_duration))
+            }
+            _ => LockParams::MULTIPLIER_DENOM as u32,
+        };
+
+        let stake_amount = member
+            .stake_amount
+            .checked_add(amount)
+            .ok_or(StakingError::Overflow)?;
+        let weighted_amount = member.weighted_amount as u128
+            + amount as u128 * added_multiplier as u128 / LockParams::MULTIPLIER_DENOM as u128;
+        let lock_multiplier = u32::try_from(
+            weighted_amount * LockParams::MULTIPLIER_DENOM as u128 / stake_amount.max(1) as u128,
+        )
+        .map_err(|_| StakingError::Overflow)?;
+
+        self.set_member_stake(member, stake_amount, lock_multiplier)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, Debug, PartialEq)]
+pub enum PenaltyPolicy {
+    Burn,
+    /// sent to a stake token account of factory authority
+    Factory,
+    /// shared among remaining stakers
+    Redistribute,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, Debug)]
+pub struct InstantUnstakeParams {
+    /// fraction of the unstaked amount, over `PENALTY_DENOM`
+    pub penalty: u16,
+    pub policy: PenaltyPolicy,
+}
+impl InstantUnstakeParams {
+    pub const LEN: usize = 2 + 1;
+
+    pub const PENALTY_DENOM: u64 = 10_000;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, Debug)]
+pub struct LockParams {
+    pub max_duration: u32,
+    /// multiplier for a lock of `max_duration`, over `MULTIPLIER_DENOM`,
+    /// shorter locks get a linearly smaller one down to 1x
+    pub max_multiplier: u32,
+}
+impl LockParams {
+    pub const LEN: usize = 4 + 4;
+
+    pub const MULTIPLIER_DENOM: u64 = 10_000;
+
+    pub fn validate_fields(&self) -> Result<()> {
+        if self.max_duration == 0 {
+            return err!(StakingError::Zero);
+        }
+        if (self.max_multiplier as u64) < Self::MULTIPLIER_DENOM {
+            return err!(StakingError::InvalidLockParams);
+        }
+
+        Ok(())
+    }
+
+    pub fn multiplier(&self, duration: u32) -> u32 {
+        let boost = (self.max_multiplier as u64 - Self::MULTIPLIER_DENOM) * duration as u64
+            / self.max_duration as u64;
+        (Self::MULTIPLIER_DENOM + boost) as u32
+    }
+}
+
+/// pool parameters not related to rewards, take effect immediately on change
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, Debug)]
+pub struct PoolSettings {
+    pub unstake_timelock: u32,
+    pub instant_unstake: Option<InstantUnstakeParams>,
+    pub lock: Option<LockParams>,
+}
+impl PoolSettings {
+    pub const LEN: usize = 4 + (1 + InstantUnstakeParams::LEN) + (1 + LockParams::LEN);
+
+    pub fn validate_fields(&self) -> Result<()> {
+        if let Some(instant_unstake) = self.instant_unstake {
+            if instant_unstake.penalty as u64 > InstantUnstakeParams::PENALTY_DENOM {
+                return err!(StakingError::InvalidPenalty);
+            }
+        }
+        if let Some(lock) = self.lock {
+            lock.validate_fields()?;
+        }
+
+        Ok(())
+    }
 }
 
+/// Configs are stored in `ConfigHistoryPage`s at
+/// `[b"config_history_page", config_history, index]` and settings changes in
+/// `SettingsHistoryPage`s at `[b"settings_history_page", config_history, index]`,
+/// so that instructions only load the pages they need
 #[account]
 pub struct ConfigHistory {
     pub bump: u8,
-    pub len: u8,
-    pub reward_params: [RewardParams; 32],
-    pub start_timestamps: [u32; 32],
+    /// start of the first config
+    pub start_ts: u32,
+    /// no rewards accrue after it, `u32::MAX` if the staking never ends
+    pub end_ts: u32,
+    pub pages_count: u32,
+    /// zero for reward streams which have no settings
+    pub settings_pages_count: u32,
 }
 impl ConfigHistory {
-    pub const LEN: usize = 1 + 1 + (RewardParams::LEN + 4) * 32;
+    pub const LEN: usize = 1 + 4 + 4 + 4 + 4;
 }
 
 #[account]
-pub struct StakesHistory {
+pub struct ConfigHistoryPage {
     pub bump: u8,
-    pub len: u8,
-    pub stakes_sums: [u64; 128],
-    /// first stakes_sum for each config
-    pub offsets: [u8; 32],
+    pub reward_params: Vec<RewardParams>,
+    pub start_timestamps: Vec<u32>,
 }
-impl StakesHistory {
-    pub const LEN: usize = 1 + 1 + 8 * 128 + 32;
+impl ConfigHistoryPage {
+    pub const MAX_LEN: usize = 16;
+
+    pub const LEN: usize = 1 + 4 + RewardParams::LEN * Self::MAX_LEN + 4 + 4 * Self::MAX_LEN;
+}
+
+#[account]
+pub struct SettingsHistoryPage {
+    pub bump: u8,
+    pub settings: Vec<PoolSettings>,
+    pub timestamps: Vec<u32>,
+}
+impl SettingsHistoryPage {
+    pub const MAX_LEN: usize = 16;
+
+    pub const LEN: usize = 1 + 4 + PoolSettings::LEN * Self::MAX_LEN + 4 + 4 * Self::MAX_LEN;
+}
+
+/// Configs loaded from consecutive pages up to the last one
+pub struct Configs {
+    /// start of the first config of the history
+    pub start_ts: u32,
+    pub reward_params: Vec<RewardParams>,
+    pub start_timestamps: Vec<u32>,
+    pub end_ts: u32,
+    /// otherwise timestamps before the first loaded config are not covered
+    pub from_first_page: bool,
+}
+impl Configs {
+    /// the next config may start after the end of the history
+    pub fn end_ts(&self, i: usize) -> u32 {
+        if i + 1 == self.reward_params.len() {
+            self.end_ts
+        } else {
+            self.start_timestamps[i + 1].min(self.end_ts)
+        }
+    }
+
+    /// index of the config active at `ts`, configs before it may be skipped
+    pub fn index_at(&self, ts: u32) -> Result<usize> {
+        if !self.from_first_page && ts < self.start_timestamps[0] {
+            return err!(StakingError::MissingConfigPages);
+        }
+
+        Ok(self
+            .start_timestamps
+            .partition_point(|&start_ts| start_ts <= ts)
+            .saturating_sub(1))
+    }
+}
+
+/// additional reward token of a staking, paid for the same weighted stakes,
+/// its config history is at `[b"config_history", reward_stream]`
+#[account]
+pub struct RewardStream {
+    pub bump: u8,
+    pub index: u8,
+    pub reward_mint: Pubkey,
+    pub reward_params: RewardParams,
+    /// same as `Staking.reward_per_share`
+    pub reward_per_share: u128,
+    /// same as `Staking.reward_per_share_ts`
+    pub reward_per_share_ts: u32,
+    pub total_funded: u64,
+    pub total_claimed: u64,
+    pub rewards_liability: u64,
+    /// same as `Staking.fixed_reserve_per_share`
+    pub fixed_reserve_per_share: u128,
+    /// same as `Staking.unclaimed_rewards`
+    pub unclaimed_rewards: u64,
+}
+impl RewardStream {
+    pub const LEN: usize = 1 + 1 + 32 + RewardParams::LEN + 16 + 4 + 8 + 8 + 8 + 16 + 8;
 }
 
 #[account]
@@ -57,9 +372,91 @@ pub struct Member {
     pub pending_amount: u64,
     pub rewards_amount: u64,
     pub last_reward_ts: u32,
-    pub pending_unstake_active: bool,
-    pub pending_unstake_end_ts: u32,
+    /// `Staking.reward_per_share` already paid out to this member
+    pub reward_per_share_paid: u128,
+    /// `Staking.penalty_per_share` already credited to this member
+    pub penalty_per_share_paid: u128,
+    /// redistributed penalties credited but not yet claimed
+    pub penalty_share_amount: u64,
+    /// slots with zero amount are free
+    pub pending_unstakes: [PendingUnstake; Member::MAX_PENDING_UNSTAKES],
+    /// stake cannot be unstaked before it
+    pub lock_end_ts: u32,
+    /// over `LockParams::MULTIPLIER_DENOM`, kept until the first settlement after the lock ends
+    pub lock_multiplier: u32,
+    /// `stake_amount` multiplied by `lock_multiplier`, used for rewards
+    pub weighted_amount: u64,
+    /// accrual state of each `RewardStream` by its index
+    pub reward_streams: [MemberRewardStream; Staking::MAX_REWARD_STREAMS],
+    /// allows anyone to compound member's rewards
+    pub auto_compound: bool,
+    /// `Staking.fixed_reserve_per_share` already settled for this member
+    pub fixed_reserve_per_share_paid: u128,
+    /// `Fixed` rewards reserved for the reward period in progress
+    pub fixed_reserve: u64,
 }
 impl Member {
-    pub const LEN: usize = 1 + 8 + 8 + 8 + 8 + 4 + 1 + 4;
+    pub const MAX_PENDING_UNSTAKES: usize = 8;
+
+    pub const LEN: usize = 1
+        + 8
+        + 8
+        + 8
+        + 8
+        + 4
+        + 16
+        + 16
+        + 8
+        + PendingUnstake::LEN * Member::MAX_PENDING_UNSTAKES
+        + 4
+        + 4
+        + 8
+        + MemberRewardStream::LEN * Staking::MAX_REWARD_STREAMS
+        + 1
+        + 16
+        + 8;
+}
+
+/// Classes of instructions that can be paused, `withdraw` is never paused
+pub struct PauseFlags;
+impl PauseFlags {
+    /// `deposit`
+    pub const DEPOSIT: u8 = 1 << 0;
+    /// `stake`, `cancel_unstake`, `lock`, `expire_lock` and `compound`
+    pub const STAKE: u8 = 1 << 1;
+    /// `start_unstake`, `instant_unstake` and `end_unstake`
+    pub const UNSTAKE: u8 = 1 << 2;
+    /// `claim_reward`, `claim_penalty_share` and `compound`
+    pub const CLAIM: u8 = 1 << 3;
+
+    pub const ALL: u8 = Self::DEPOSIT | Self::STAKE | Self::UNSTAKE | Self::CLAIM;
+
+    pub fn validate(flags: u8) -> Result<()> {
+        if flags & !Self::ALL != 0 {
+            return err!(StakingError::InvalidPauseFlags);
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, Default)]
+pub struct PendingUnstake {
+    pub amount: u64,
+    pub end_ts: u32,
+}
+impl PendingUnstake {
+    pub const LEN: usize = 8 + 4;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, Default)]
+pub struct MemberRewardStream {
+    pub rewards_amount: u64,
+    pub last_reward_ts: u32,
+    pub reward_per_share_paid: u128,
+    pub fixed_reserve_per_share_paid: u128,
+    pub fixed_reserve: u64,
+}
+impl MemberRewardStream {
+    pub const LEN: usize = 8 + 4 + 16 + 16 + 8;
 }
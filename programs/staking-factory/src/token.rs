@@ -0,0 +1,193 @@
+use crate::error::*;
+use anchor_lang::{
+    prelude::*,
+    solana_program::{
+        instruction::Instruction,
+        program::{invoke, invoke_signed},
+    },
+    system_program,
+};
+use anchor_spl::associated_token;
+use spl_token_2022::{
+    extension::{BaseState, ExtensionType, StateWithExtensions},
+    state::{Account as TokenAccountState, Mint as MintState},
+};
+
+/// Mint extensions that do not break the staking's accounting:
+/// transfer fees are handled by crediting the amounts received
+const SUPPORTED_MINT_EXTENSIONS: [ExtensionType; 2] = [
+    ExtensionType::TransferFeeConfig,
+    ExtensionType::MintCloseAuthority,
+];
+
+/// Checks that `token_program` is the token program or Token-2022
+pub fn check_token_program(token_program: &AccountInfo) -> Result<()> {
+    if token_program.key() != anchor_spl::token::ID && token_program.key() != spl_token_2022::ID {
+        return err!(StakingError::InvalidTokenProgram);
+    }
+
+    Ok(())
+}
+
+fn load_state<S: BaseState>(account: &AccountInfo, token_program: &AccountInfo) -> Result<S> {
+    check_token_program(token_program)?;
+    if account.owner != token_program.key {
+        return err!(StakingError::InvalidTokenProgram);
+    }
+
+    let data = account.try_borrow_data()?;
+    Ok(StateWithExtensions::<S>::unpack(&data)?.base)
+}
+
+/// Loads a mint of either token program, `token_program` must own it
+pub fn load_mint(mint: &AccountInfo, token_program: &AccountInfo) -> Result<MintState> {
+    load_state::<MintState>(mint, token_program)
+}
+
+/// Loads a token account of either token program, `token_program` must own it
+pub fn load_token_account(
+    account: &AccountInfo,
+    token_program: &AccountInfo,
+) -> Result<TokenAccountState> {
+    load_state::<TokenAccountState>(account, token_program)
+}
+
+/// Same as `load_token_account` checking the mint and owner of the account
+pub fn load_token_account_of(
+    account: &AccountInfo,
+    token_program: &AccountInfo,
+    mint: &Pubkey,
+    owner: &Pubkey,
+) -> Result<TokenAccountState> {
+    let token_account = load_token_account(account, token_program)?;
+    if token_account.mint != *mint || token_account.owner != *owner {
+        return err!(StakingError::InvalidTokenAccount);
+    }
+
+    Ok(token_account)
+}
+
+/// Associated token account of `owner` for a mint of `token_program`,
+/// same as `get_associated_token_address` for the token program
+pub fn associated_token_address(owner: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[owner.as_ref(), token_program.as_ref(), mint.as_ref()],
+        &associated_token::ID,
+    )
+    .0
+}
+
+/// Same as `load_token_account_of` for the associated token account of `owner`
+pub fn load_associated_token_account(
+    account: &AccountInfo,
+    token_program: &AccountInfo,
+    mint: &Pubkey,
+    owner: &Pubkey,
+) -> Result<TokenAccountState> {
+    if account.key() != associated_token_address(owner, mint, token_program.key) {
+        return err!(StakingError::InvalidTokenAccount);
+    }
+
+    load_token_account_of(account, token_program, mint, owner)
+}
+
+/// Rejects mints with extensions not in `SUPPORTED_MINT_EXTENSIONS`
+pub fn check_mint_extensions(mint: &AccountInfo) -> Result<()> {
+    let data = mint.try_borrow_data()?;
+    let extension_types = StateWithExtensions::<MintState>::unpack(&data)?.get_extension_types()?;
+    if extension_types
+        .iter()
+        .any(|extension_type| !SUPPORTED_MINT_EXTENSIONS.contains(extension_type))
+    {
+        return err!(StakingError::UnsupportedMintExtension);
+    }
+
+    Ok(())
+}
+
+/// Creates the associated token account of `owner` for a mint of `token_program`
+/// unless it exists, an existing one is checked
+#[allow(clippy::too_many_arguments)]
+pub fn init_associated_token_account<'info>(
+    payer: &AccountInfo<'info>,
+    account: &AccountInfo<'info>,
+    owner: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    associated_token_program: &AccountInfo<'info>,
+    rent: &AccountInfo<'info>,
+) -> Result<()> {
+    if account.owner != &system_program::ID {
+        load_associated_token_account(account, token_program, mint.key, owner.key)?;
+        return Ok(());
+    }
+    if account.key() != associated_token_address(owner.key, mint.key, token_program.key) {
+        return err!(StakingError::InvalidTokenAccount);
+    }
+
+    let ix = Instruction {
+        program_id: associated_token::ID,
+        accounts: vec![
+            AccountMeta::new(payer.key(), true),
+            AccountMeta::new(account.key(), false),
+            AccountMeta::new_readonly(owner.key(), false),
+            AccountMeta::new_readonly(mint.key(), false),
+            AccountMeta::new_readonly(system_program.key(), false),
+            AccountMeta::new_readonly(token_program.key(), false),
+            AccountMeta::new_readonly(rent.key(), false),
+        ],
+        data: vec![],
+    };
+    invoke(
+        &ix,
+        &[
+            payer.clone(),
+            account.clone(),
+            owner.clone(),
+            mint.clone(),
+            system_program.clone(),
+            token_program.clone(),
+            rent.clone(),
+            associated_token_program.clone(),
+        ],
+    )
+    .map_err(Into::into)
+}
+
+/// `TransferChecked` of either token program, signed with `signer` seeds if any
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_checked<'info>(
+    token_program: &AccountInfo<'info>,
+    from: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    signer: &[&[&[u8]]],
+    amount: u64,
+    decimals: u8,
+) -> Result<()> {
+    check_token_program(token_program)?;
+    let ix = spl_token_2022::instruction::transfer_checked(
+        token_program.key,
+        from.key,
+        mint.key,
+        to.key,
+        authority.key,
+        &[],
+        amount,
+        decimals,
+    )?;
+    invoke_signed(
+        &ix,
+        &[
+            from.clone(),
+            mint.clone(),
+            to.clone(),
+            authority.clone(),
+            token_program.clone(),
+        ],
+        signer,
+    )
+    .map_err(Into::into)
+}
@@ -1,12 +1,21 @@
 import { BN } from "@project-serum/anchor";
-import { Keypair, SystemProgram } from "@solana/web3.js";
-import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import {
+  Keypair,
+  PublicKey,
+  SystemProgram,
+  SYSVAR_RENT_PUBKEY,
+} from "@solana/web3.js";
+import {
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  getAssociatedTokenAddress,
+  TOKEN_PROGRAM_ID,
+} from "@solana/spl-token";
 import { Context } from "./ctx";
-import { mintTo } from "./token";
+import { findATA, mintTo } from "./token";
 
-export async function initialize(ctx: Context): Promise<void> {
+export async function initialize(ctx: Context, fee: number): Promise<void> {
   await ctx.program.methods
-    .initialize()
+    .initialize(fee)
     .accounts({
       factory: ctx.factory,
       authority: ctx.factoryAuthority.publicKey,
@@ -16,46 +25,375 @@ export async function initialize(ctx: Context): Promise<void> {
     .rpc();
 }
 
+export async function changeFactoryFee(
+  ctx: Context,
+  fee: number
+): Promise<void> {
+  await ctx.program.methods
+    .changeFactoryFee(fee)
+    .accounts({
+      factory: ctx.factory,
+      authority: ctx.factoryAuthority.publicKey,
+    })
+    .signers([ctx.factoryAuthority])
+    .rpc();
+}
+
+export async function changeStakingFee(
+  ctx: Context,
+  feeOverride: number | null
+): Promise<void> {
+  await ctx.program.methods
+    .changeStakingFee(feeOverride)
+    .accounts({
+      factory: ctx.factory,
+      staking: await ctx.staking(),
+      authority: ctx.factoryAuthority.publicKey,
+    })
+    .signers([ctx.factoryAuthority])
+    .rpc();
+}
+
+export async function proposeFactoryAuthority(
+  ctx: Context,
+  authority: Keypair,
+  pendingAuthority: PublicKey | null
+): Promise<void> {
+  await ctx.program.methods
+    .proposeFactoryAuthority(pendingAuthority)
+    .accounts({
+      factory: ctx.factory,
+      authority: authority.publicKey,
+    })
+    .signers([authority])
+    .rpc();
+}
+
+export async function acceptFactoryAuthority(
+  ctx: Context,
+  newAuthority: Keypair
+): Promise<void> {
+  await ctx.program.methods
+    .acceptFactoryAuthority()
+    .accounts({
+      factory: ctx.factory,
+      newAuthority: newAuthority.publicKey,
+    })
+    .signers([newAuthority])
+    .rpc();
+}
+
 export async function createStaking(
   ctx: Context,
   unstakeTimelock: number,
-  rewardType: any
+  rewardType: any,
+  startTs: number | null = null,
+  endTs: number | null = null,
+  instantUnstake: any = null,
+  lock: any = null
 ): Promise<void> {
   ctx.stakingId = (
     await ctx.program.account.factory.fetch(ctx.factory)
   ).stakingsCount;
 
+  const configHistory = await ctx.configHistory();
   await ctx.program.methods
-    .createStaking(ctx.stakeMint, ctx.rewardMint, unstakeTimelock, rewardType)
+    .createStaking(
+      rewardType,
+      { unstakeTimelock, instantUnstake, lock },
+      startTs,
+      endTs
+    )
     .accounts({
       factory: ctx.factory,
       staking: await ctx.staking(),
-      configHistory: await ctx.configHistory(),
-      stakesHistory: await ctx.stakesHistory(),
+      configHistory,
+      configHistoryPage: await ctx.configHistoryPage(configHistory, 0),
+      settingsHistoryPage: await ctx.settingsHistoryPage(configHistory, 0),
+      stakeMint: ctx.stakeMint,
+      rewardMint: ctx.rewardMint,
+      stakingVault: await getAssociatedTokenAddress(
+        ctx.rewardMint,
+        await ctx.staking(),
+        true
+      ),
+      stakeVault: await getAssociatedTokenAddress(
+        ctx.stakeMint,
+        await ctx.staking(),
+        true
+      ),
       authority: ctx.stakingAuthority.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      stakeTokenProgram: TOKEN_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
       systemProgram: SystemProgram.programId,
+      rent: SYSVAR_RENT_PUBKEY,
     })
     .signers([ctx.stakingAuthority])
     .rpc();
 
+  await fundRewards(ctx, 1_000_000);
+}
+
+export async function fundRewards(
+  ctx: Context,
+  amount: number | BN
+): Promise<void> {
   await mintTo(
     ctx,
-    await ctx.rewardATA(await ctx.staking()),
+    await ctx.rewardATA(ctx.stakingAuthority.publicKey),
     ctx.mintAuthority,
-    1_000_000
+    Number(amount)
   );
+
+  await ctx.program.methods
+    .fundRewards(new BN(amount))
+    .accounts({
+      staking: await ctx.staking(),
+      stakingVault: await ctx.rewardATA(await ctx.staking()),
+      funder: ctx.stakingAuthority.publicKey,
+      from: await ctx.rewardATA(ctx.stakingAuthority.publicKey),
+      rewardMint: ctx.rewardMint,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .signers([ctx.stakingAuthority])
+    .rpc();
+}
+
+export async function extendStaking(
+  ctx: Context,
+  endTs: number
+): Promise<void> {
+  await ctx.program.methods
+    .extendStaking(endTs)
+    .accounts({
+      staking: await ctx.staking(),
+      configHistory: await ctx.configHistory(),
+      authority: ctx.stakingAuthority.publicKey,
+    })
+    .signers([ctx.stakingAuthority])
+    .rpc();
+}
+
+export async function sunsetStaking(
+  ctx: Context,
+  endTs: number | null = null
+): Promise<void> {
+  const { rewardStreamsCount } = await ctx.program.account.staking.fetch(
+    await ctx.staking()
+  );
+  const accounts = [];
+  for (let i = 0; i < rewardStreamsCount; i++) {
+    accounts.push(
+      { pubkey: await ctx.rewardStream(i), isWritable: false, isSigner: false },
+      {
+        pubkey: await ctx.rewardStreamConfigHistory(i),
+        isWritable: true,
+        isSigner: false,
+      }
+    );
+  }
+
+  await ctx.program.methods
+    .sunsetStaking(endTs)
+    .accounts({
+      staking: await ctx.staking(),
+      configHistory: await ctx.configHistory(),
+      authority: ctx.stakingAuthority.publicKey,
+    })
+    .remainingAccounts(accounts)
+    .signers([ctx.stakingAuthority])
+    .rpc();
+}
+
+export async function closeStaking(
+  ctx: Context,
+  streams: number[] = []
+): Promise<void> {
+  const configHistory = await ctx.configHistory();
+  const accounts = await ctx.configHistoryPages(configHistory, true, true);
+  for (const index of streams) {
+    const rewardStream = await ctx.rewardStream(index);
+    const streamConfigHistory = await ctx.rewardStreamConfigHistory(index);
+    const { rewardMint } = await ctx.program.account.rewardStream.fetch(
+      rewardStream
+    );
+    for (const pubkey of [rewardStream, streamConfigHistory]) {
+      accounts.push({ pubkey, isWritable: true, isSigner: false });
+    }
+    accounts.push(
+      ...(await ctx.configHistoryPages(streamConfigHistory, false, true))
+    );
+    for (const pubkey of [
+      await findATA(ctx, await ctx.staking(), rewardMint),
+      await findATA(ctx, ctx.stakingAuthority.publicKey, rewardMint),
+    ]) {
+      accounts.push({ pubkey, isWritable: true, isSigner: false });
+    }
+  }
+
+  await ctx.program.methods
+    .closeStaking()
+    .accounts({
+      staking: await ctx.staking(),
+      configHistory,
+      stakingVault: await ctx.rewardATA(await ctx.staking()),
+      stakeVault: await ctx.stakeATA(await ctx.staking()),
+      authority: ctx.stakingAuthority.publicKey,
+      to: await ctx.rewardATA(ctx.stakingAuthority.publicKey),
+      stakeTo: await ctx.stakeATA(ctx.stakingAuthority.publicKey),
+      tokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .remainingAccounts(accounts)
+    .signers([ctx.stakingAuthority])
+    .rpc();
+}
+
+export async function setFactoryPaused(
+  ctx: Context,
+  paused: number
+): Promise<void> {
+  await ctx.program.methods
+    .setFactoryPaused(paused)
+    .accounts({
+      factory: ctx.factory,
+      authority: ctx.factoryAuthority.publicKey,
+    })
+    .signers([ctx.factoryAuthority])
+    .rpc();
+}
+
+export async function setStakingPaused(
+  ctx: Context,
+  authority: Keypair,
+  paused: number
+): Promise<void> {
+  await ctx.program.methods
+    .setStakingPaused(paused)
+    .accounts({
+      factory: ctx.factory,
+      staking: await ctx.staking(),
+      authority: authority.publicKey,
+    })
+    .signers([authority])
+    .rpc();
+}
+
+export async function proposeStakingAuthority(
+  ctx: Context,
+  authority: Keypair,
+  pendingAuthority: PublicKey | null
+): Promise<void> {
+  await ctx.program.methods
+    .proposeStakingAuthority(pendingAuthority)
+    .accounts({
+      staking: await ctx.staking(),
+      authority: authority.publicKey,
+    })
+    .signers([authority])
+    .rpc();
+}
+
+export async function acceptStakingAuthority(
+  ctx: Context,
+  newAuthority: Keypair
+): Promise<void> {
+  await ctx.program.methods
+    .acceptStakingAuthority()
+    .accounts({
+      staking: await ctx.staking(),
+      newAuthority: newAuthority.publicKey,
+    })
+    .signers([newAuthority])
+    .rpc();
 }
 
 export async function changeConfig(
   ctx: Context,
+  rewardType: any,
+  settings: any = null
+): Promise<void> {
+  const configHistory = await ctx.configHistory();
+  const { pagesCount, settingsPagesCount } =
+    await ctx.program.account.configHistory.fetch(configHistory);
+
+  await ctx.program.methods
+    .changeConfig(rewardType, settings)
+    .accounts({
+      staking: await ctx.staking(),
+      configHistory,
+      lastPage: await ctx.configHistoryPage(configHistory, pagesCount - 1),
+      nextPage: await ctx.configHistoryPage(configHistory, pagesCount),
+      lastSettingsPage: await ctx.settingsHistoryPage(
+        configHistory,
+        settingsPagesCount - 1
+      ),
+      nextSettingsPage: await ctx.settingsHistoryPage(
+        configHistory,
+        settingsPagesCount
+      ),
+      authority: ctx.stakingAuthority.publicKey,
+      systemProgram: SystemProgram.programId,
+    })
+    .signers([ctx.stakingAuthority])
+    .rpc();
+}
+
+export async function addRewardStream(
+  ctx: Context,
+  rewardMint: PublicKey,
+  rewardType: any,
+  startTs: number | null = null,
+  endTs: number | null = null
+): Promise<void> {
+  const { rewardStreamsCount } = await ctx.program.account.staking.fetch(
+    await ctx.staking()
+  );
+  const configHistory = await ctx.rewardStreamConfigHistory(
+    rewardStreamsCount
+  );
+
+  await ctx.program.methods
+    .addRewardStream(rewardType, startTs, endTs)
+    .accounts({
+      staking: await ctx.staking(),
+      rewardStream: await ctx.rewardStream(rewardStreamsCount),
+      configHistory,
+      configHistoryPage: await ctx.configHistoryPage(configHistory, 0),
+      rewardMint,
+      streamVault: await getAssociatedTokenAddress(
+        rewardMint,
+        await ctx.staking(),
+        true
+      ),
+      authority: ctx.stakingAuthority.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+      rent: SYSVAR_RENT_PUBKEY,
+    })
+    .signers([ctx.stakingAuthority])
+    .rpc();
+}
+
+export async function changeStreamConfig(
+  ctx: Context,
+  index: number,
   rewardType: any
 ): Promise<void> {
+  const configHistory = await ctx.rewardStreamConfigHistory(index);
+  const { pagesCount } = await ctx.program.account.configHistory.fetch(
+    configHistory
+  );
+
   await ctx.program.methods
-    .changeConfig(rewardType)
+    .changeStreamConfig(rewardType)
     .accounts({
       staking: await ctx.staking(),
-      configHistory: await ctx.configHistory(),
-      stakesHistory: await ctx.stakesHistory(),
+      rewardStream: await ctx.rewardStream(index),
+      configHistory,
+      lastPage: await ctx.configHistoryPage(configHistory, pagesCount - 1),
+      nextPage: await ctx.configHistoryPage(configHistory, pagesCount),
       authority: ctx.stakingAuthority.publicKey,
       systemProgram: SystemProgram.programId,
     })
@@ -63,6 +401,89 @@ export async function changeConfig(
     .rpc();
 }
 
+export async function extendRewardStream(
+  ctx: Context,
+  index: number,
+  endTs: number
+): Promise<void> {
+  await ctx.program.methods
+    .extendRewardStream(endTs)
+    .accounts({
+      staking: await ctx.staking(),
+      rewardStream: await ctx.rewardStream(index),
+      configHistory: await ctx.rewardStreamConfigHistory(index),
+      authority: ctx.stakingAuthority.publicKey,
+    })
+    .signers([ctx.stakingAuthority])
+    .rpc();
+}
+
+export async function fundRewardStream(
+  ctx: Context,
+  index: number,
+  rewardMint: PublicKey,
+  amount: number | BN
+): Promise<void> {
+  const from = await findATA(ctx, ctx.stakingAuthority.publicKey, rewardMint);
+  await mintTo(ctx, from, ctx.mintAuthority, Number(amount));
+
+  await ctx.program.methods
+    .fundRewardStream(new BN(amount))
+    .accounts({
+      staking: await ctx.staking(),
+      rewardStream: await ctx.rewardStream(index),
+      streamVault: await findATA(ctx, await ctx.staking(), rewardMint),
+      funder: ctx.stakingAuthority.publicKey,
+      from,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .signers([ctx.stakingAuthority])
+    .rpc();
+}
+
+export async function withdrawExcessRewards(
+  ctx: Context,
+  amount: number | BN
+): Promise<void> {
+  await ctx.program.methods
+    .withdrawExcessRewards(new BN(amount))
+    .accounts({
+      staking: await ctx.staking(),
+      configHistory: await ctx.configHistory(),
+      stakingVault: await ctx.rewardATA(await ctx.staking()),
+      authority: ctx.stakingAuthority.publicKey,
+      to: await ctx.rewardATA(ctx.stakingAuthority.publicKey),
+      tokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .remainingAccounts(await ctx.configHistoryPages(await ctx.configHistory()))
+    .signers([ctx.stakingAuthority])
+    .rpc();
+}
+
+export async function withdrawExcessStreamRewards(
+  ctx: Context,
+  index: number,
+  rewardMint: PublicKey,
+  amount: number | BN
+): Promise<void> {
+  const configHistory = await ctx.rewardStreamConfigHistory(index);
+
+  await ctx.program.methods
+    .withdrawExcessStreamRewards(new BN(amount))
+    .accounts({
+      staking: await ctx.staking(),
+      rewardStream: await ctx.rewardStream(index),
+      configHistory,
+      streamVault: await findATA(ctx, await ctx.staking(), rewardMint),
+      authority: ctx.stakingAuthority.publicKey,
+      to: await findATA(ctx, ctx.stakingAuthority.publicKey, rewardMint),
+      tokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .remainingAccounts(await ctx.configHistoryPages(configHistory))
+    .signers([ctx.stakingAuthority])
+    .rpc();
+}
+
 export async function registerMember(
   ctx: Context,
   beneficiary: Keypair
@@ -94,11 +515,13 @@ export async function deposit(
   await ctx.program.methods
     .deposit(new BN(amount))
     .accounts({
+      factory: ctx.factory,
       staking: await ctx.staking(),
       beneficiary: beneficiary.publicKey,
       member: await ctx.member(beneficiary.publicKey),
       from: await ctx.stakeATA(beneficiary.publicKey),
       memberVault: await ctx.stakeATA(await ctx.member(beneficiary.publicKey)),
+      stakeMint: ctx.stakeMint,
       tokenProgram: TOKEN_PROGRAM_ID,
     })
     .signers([beneficiary])
@@ -113,12 +536,43 @@ export async function stake(
   await ctx.program.methods
     .stake(new BN(amount))
     .accounts({
+      factory: ctx.factory,
+      staking: await ctx.staking(),
+      configHistory: await ctx.configHistory(),
+      beneficiary: beneficiary.publicKey,
+      member: await ctx.member(beneficiary.publicKey),
+    })
+    .remainingAccounts(await ctx.rewardAccounts())
+    .signers([beneficiary])
+    .rpc();
+}
+
+export async function depositAndStake(
+  ctx: Context,
+  beneficiary: Keypair,
+  amount: number | BN
+): Promise<void> {
+  await mintTo(
+    ctx,
+    await ctx.stakeATA(beneficiary.publicKey),
+    ctx.mintAuthority,
+    Number(amount)
+  );
+
+  await ctx.program.methods
+    .depositAndStake(new BN(amount))
+    .accounts({
+      factory: ctx.factory,
       staking: await ctx.staking(),
       configHistory: await ctx.configHistory(),
-      stakesHistory: await ctx.stakesHistory(),
       beneficiary: beneficiary.publicKey,
       member: await ctx.member(beneficiary.publicKey),
+      from: await ctx.stakeATA(beneficiary.publicKey),
+      memberVault: await ctx.stakeATA(await ctx.member(beneficiary.publicKey)),
+      stakeMint: ctx.stakeMint,
+      tokenProgram: TOKEN_PROGRAM_ID,
     })
+    .remainingAccounts(await ctx.rewardAccounts())
     .signers([beneficiary])
     .rpc();
 }
@@ -135,16 +589,127 @@ export async function claimReward(
       staking: await ctx.staking(),
       stakingVault: await ctx.rewardATA(await ctx.staking()),
       configHistory: await ctx.configHistory(),
-      stakesHistory: await ctx.stakesHistory(),
       beneficiary: beneficiary.publicKey,
       member: await ctx.member(beneficiary.publicKey),
       to: await ctx.rewardATA(beneficiary.publicKey),
+      rewardMint: ctx.rewardMint,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .remainingAccounts(await ctx.rewardAccounts())
+    .signers([beneficiary])
+    .rpc();
+}
+
+export async function claimStreamReward(
+  ctx: Context,
+  beneficiary: Keypair,
+  index: number
+): Promise<void> {
+  const rewardStream = await ctx.rewardStream(index);
+  const configHistory = await ctx.rewardStreamConfigHistory(index);
+  const { rewardMint } = await ctx.program.account.rewardStream.fetch(
+    rewardStream
+  );
+
+  await ctx.program.methods
+    .claimStreamReward()
+    .accounts({
+      factory: ctx.factory,
+      factoryVault: await findATA(
+        ctx,
+        ctx.factoryAuthority.publicKey,
+        rewardMint
+      ),
+      staking: await ctx.staking(),
+      rewardStream,
+      configHistory,
+      streamVault: await findATA(ctx, await ctx.staking(), rewardMint),
+      beneficiary: beneficiary.publicKey,
+      member: await ctx.member(beneficiary.publicKey),
+      to: await findATA(ctx, beneficiary.publicKey, rewardMint),
       tokenProgram: TOKEN_PROGRAM_ID,
     })
+    .remainingAccounts(await ctx.configHistoryPages(configHistory))
     .signers([beneficiary])
     .rpc();
 }
 
+export async function compound(
+  ctx: Context,
+  caller: Keypair,
+  beneficiary: PublicKey
+): Promise<void> {
+  await ctx.program.methods
+    .compound()
+    .accounts({
+      factory: ctx.factory,
+      factoryVault: ctx.factoryVault,
+      staking: await ctx.staking(),
+      stakingVault: await ctx.rewardATA(await ctx.staking()),
+      configHistory: await ctx.configHistory(),
+      caller: caller.publicKey,
+      beneficiary,
+      member: await ctx.member(beneficiary),
+      memberVault: await ctx.stakeATA(await ctx.member(beneficiary)),
+      tokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .remainingAccounts(await ctx.rewardAccounts())
+    .signers([caller])
+    .rpc();
+}
+
+export async function setAutoCompound(
+  ctx: Context,
+  beneficiary: Keypair,
+  autoCompound: boolean
+): Promise<void> {
+  await ctx.program.methods
+    .setAutoCompound(autoCompound)
+    .accounts({
+      staking: await ctx.staking(),
+      beneficiary: beneficiary.publicKey,
+      member: await ctx.member(beneficiary.publicKey),
+    })
+    .signers([beneficiary])
+    .rpc();
+}
+
+export async function lock(
+  ctx: Context,
+  beneficiary: Keypair,
+  duration: number
+): Promise<void> {
+  await ctx.program.methods
+    .lock(duration)
+    .accounts({
+      factory: ctx.factory,
+      staking: await ctx.staking(),
+      configHistory: await ctx.configHistory(),
+      beneficiary: beneficiary.publicKey,
+      member: await ctx.member(beneficiary.publicKey),
+    })
+    .remainingAccounts(await ctx.rewardAccounts())
+    .signers([beneficiary])
+    .rpc();
+}
+
+export async function expireLock(
+  ctx: Context,
+  beneficiary: PublicKey
+): Promise<void> {
+  await ctx.program.methods
+    .expireLock()
+    .accounts({
+      factory: ctx.factory,
+      staking: await ctx.staking(),
+      configHistory: await ctx.configHistory(),
+      beneficiary,
+      member: await ctx.member(beneficiary),
+    })
+    .remainingAccounts(await ctx.rewardAccounts())
+    .rpc();
+}
+
 export async function startUnstake(
   ctx: Context,
   beneficiary: Keypair,
@@ -153,12 +718,75 @@ export async function startUnstake(
   await ctx.program.methods
     .startUnstake(new BN(amount))
     .accounts({
+      factory: ctx.factory,
+      staking: await ctx.staking(),
+      configHistory: await ctx.configHistory(),
+      beneficiary: beneficiary.publicKey,
+      member: await ctx.member(beneficiary.publicKey),
+    })
+    .remainingAccounts(await ctx.rewardAccounts())
+    .signers([beneficiary])
+    .rpc();
+}
+
+export async function cancelUnstake(
+  ctx: Context,
+  beneficiary: Keypair
+): Promise<void> {
+  await ctx.program.methods
+    .cancelUnstake()
+    .accounts({
+      factory: ctx.factory,
+      staking: await ctx.staking(),
+      configHistory: await ctx.configHistory(),
+      beneficiary: beneficiary.publicKey,
+      member: await ctx.member(beneficiary.publicKey),
+    })
+    .remainingAccounts(await ctx.rewardAccounts())
+    .signers([beneficiary])
+    .rpc();
+}
+
+export async function instantUnstake(
+  ctx: Context,
+  beneficiary: Keypair,
+  amount: number | BN,
+  penaltyDestination: PublicKey
+): Promise<void> {
+  await ctx.program.methods
+    .instantUnstake(new BN(amount))
+    .accounts({
+      factory: ctx.factory,
       staking: await ctx.staking(),
       configHistory: await ctx.configHistory(),
-      stakesHistory: await ctx.stakesHistory(),
       beneficiary: beneficiary.publicKey,
       member: await ctx.member(beneficiary.publicKey),
+      memberVault: await ctx.stakeATA(await ctx.member(beneficiary.publicKey)),
+      penaltyDestination,
+      tokenProgram: TOKEN_PROGRAM_ID,
     })
+    .remainingAccounts(await ctx.rewardAccounts())
+    .signers([beneficiary])
+    .rpc();
+}
+
+export async function claimPenaltyShare(
+  ctx: Context,
+  beneficiary: Keypair
+): Promise<void> {
+  await ctx.program.methods
+    .claimPenaltyShare()
+    .accounts({
+      factory: ctx.factory,
+      staking: await ctx.staking(),
+      configHistory: await ctx.configHistory(),
+      stakingStakeVault: await ctx.stakeATA(await ctx.staking()),
+      beneficiary: beneficiary.publicKey,
+      member: await ctx.member(beneficiary.publicKey),
+      memberVault: await ctx.stakeATA(await ctx.member(beneficiary.publicKey)),
+      tokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .remainingAccounts(await ctx.rewardAccounts())
     .signers([beneficiary])
     .rpc();
 }
@@ -170,6 +798,7 @@ export async function endUnstake(
   await ctx.program.methods
     .endUnstake()
     .accounts({
+      factory: ctx.factory,
       staking: await ctx.staking(),
       beneficiary: beneficiary.publicKey,
       member: await ctx.member(beneficiary.publicKey),
@@ -191,8 +820,80 @@ export async function withdraw(
       member: await ctx.member(beneficiary.publicKey),
       memberVault: await ctx.stakeATA(await ctx.member(beneficiary.publicKey)),
       to: await ctx.stakeATA(beneficiary.publicKey),
+      stakeMint: ctx.stakeMint,
       tokenProgram: TOKEN_PROGRAM_ID,
     })
     .signers([beneficiary])
     .rpc();
 }
+
+export async function endUnstakeAndWithdraw(
+  ctx: Context,
+  beneficiary: Keypair
+): Promise<void> {
+  await ctx.program.methods
+    .endUnstakeAndWithdraw()
+    .accounts({
+      factory: ctx.factory,
+      staking: await ctx.staking(),
+      beneficiary: beneficiary.publicKey,
+      member: await ctx.member(beneficiary.publicKey),
+      memberVault: await ctx.stakeATA(await ctx.member(beneficiary.publicKey)),
+      to: await ctx.stakeATA(beneficiary.publicKey),
+      stakeMint: ctx.stakeMint,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .signers([beneficiary])
+    .rpc();
+}
+
+export async function closeMember(
+  ctx: Context,
+  beneficiary: Keypair
+): Promise<void> {
+  await ctx.program.methods
+    .closeMember()
+    .accounts({
+      staking: await ctx.staking(),
+      beneficiary: beneficiary.publicKey,
+      member: await ctx.member(beneficiary.publicKey),
+      memberVault: await ctx.stakeATA(await ctx.member(beneficiary.publicKey)),
+      to: await ctx.stakeATA(beneficiary.publicKey),
+      tokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .remainingAccounts(await ctx.rewardStreamsAccounts())
+    .signers([beneficiary])
+    .rpc();
+}
+
+export async function setEmergencyUnstake(
+  ctx: Context,
+  authority: Keypair,
+  enabled: boolean
+): Promise<void> {
+  await ctx.program.methods
+    .setEmergencyUnstake(enabled)
+    .accounts({
+      factory: ctx.factory,
+      staking: await ctx.staking(),
+      authority: authority.publicKey,
+    })
+    .signers([authority])
+    .rpc();
+}
+
+export async function emergencyUnstake(
+  ctx: Context,
+  beneficiary: Keypair
+): Promise<void> {
+  await ctx.program.methods
+    .emergencyUnstake()
+    .accounts({
+      staking: await ctx.staking(),
+      beneficiary: beneficiary.publicKey,
+      member: await ctx.member(beneficiary.publicKey),
+    })
+    .remainingAccounts(await ctx.rewardStreamsAccounts())
+    .signers([beneficiary])
+    .rpc();
+}
@@ -1,6 +1,6 @@
 import * as anchor from "@project-serum/anchor";
 import { BN, Program } from "@project-serum/anchor";
-import { Connection, Keypair, PublicKey } from "@solana/web3.js";
+import { AccountMeta, Connection, Keypair, PublicKey } from "@solana/web3.js";
 import { StakingFactory } from "../target/types/staking_factory";
 import { burnAll, createMint, findATA, TokenAccount } from "./token";
 import { airdrop, findPDA } from "./utils";
@@ -85,13 +85,110 @@ export class Context {
     ]);
   }
 
-  async stakesHistory(): Promise<PublicKey> {
+  async rewardStream(index: number): Promise<PublicKey> {
     return await findPDA(this, [
-      Buffer.from("stakes_history"),
+      Buffer.from("reward_stream"),
       (await this.staking()).toBuffer(),
+      Buffer.from([index]),
     ]);
   }
 
+  async rewardStreamConfigHistory(index: number): Promise<PublicKey> {
+    return await findPDA(this, [
+      Buffer.from("config_history"),
+      (await this.rewardStream(index)).toBuffer(),
+    ]);
+  }
+
+  async configHistoryPage(
+    configHistory: PublicKey,
+    index: number
+  ): Promise<PublicKey> {
+    return await findPDA(this, [
+      Buffer.from("config_history_page"),
+      configHistory.toBuffer(),
+      new BN(index).toArrayLike(Buffer, "le", 4),
+    ]);
+  }
+
+  async settingsHistoryPage(
+    configHistory: PublicKey,
+    index: number
+  ): Promise<PublicKey> {
+    return await findPDA(this, [
+      Buffer.from("settings_history_page"),
+      configHistory.toBuffer(),
+      new BN(index).toArrayLike(Buffer, "le", 4),
+    ]);
+  }
+
+  // all config history pages, also the settings ones if `settings` is set
+  async configHistoryPages(
+    configHistory: PublicKey,
+    settings = false,
+    isWritable = false
+  ): Promise<AccountMeta[]> {
+    const { pagesCount, settingsPagesCount } =
+      await this.program.account.configHistory.fetch(configHistory);
+    const accounts = [];
+    for (let i = 0; i < pagesCount; i++) {
+      accounts.push({
+        pubkey: await this.configHistoryPage(configHistory, i),
+        isWritable,
+        isSigner: false,
+      });
+    }
+    for (let i = 0; settings && i < settingsPagesCount; i++) {
+      accounts.push({
+        pubkey: await this.settingsHistoryPage(configHistory, i),
+        isWritable,
+        isSigner: false,
+      });
+    }
+    return accounts;
+  }
+
+  // a reward stream, its config history and pages
+  async rewardStreamAccounts(index: number): Promise<AccountMeta[]> {
+    const configHistory = await this.rewardStreamConfigHistory(index);
+    return [
+      {
+        pubkey: await this.rewardStream(index),
+        isWritable: true,
+        isSigner: false,
+      },
+      { pubkey: configHistory, isWritable: false, isSigner: false },
+      ...(await this.configHistoryPages(configHistory)),
+    ];
+  }
+
+  // config history pages and all reward streams to settle on stake changes
+  async rewardAccounts(): Promise<AccountMeta[]> {
+    const { rewardStreamsCount } = await this.program.account.staking.fetch(
+      await this.staking()
+    );
+    const accounts = await this.configHistoryPages(await this.configHistory());
+    for (let i = 0; i < rewardStreamsCount; i++) {
+      accounts.push(...(await this.rewardStreamAccounts(i)));
+    }
+    return accounts;
+  }
+
+  async rewardStreamsAccounts(): Promise<AccountMeta[]> {
+    const { rewardStreamsCount } = await this.program.account.staking.fetch(
+      await this.staking()
+    );
+    const accounts: AccountMeta[] = [];
+    for (let i = 0; i < rewardStreamsCount; i++) {
+      accounts.push({
+        pubkey: await this.rewardStream(i),
+        isWritable: true,
+        isSigner: false,
+      });
+    }
+    return accounts;
+  }
+
   async member(user: PublicKey): Promise<PublicKey> {
     return await findPDA(this, [
       Buffer.from("member"),
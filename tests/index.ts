@@ -4,17 +4,50 @@ import * as chai from "chai";
 import chaiAsPromised from "chai-as-promised";
 import { sleep } from "./utils";
 import { Context } from "./ctx";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { getAssociatedTokenAddress } from "@solana/spl-token";
+import { createMint, findATA, mintTo, TokenAccount } from "./token";
 import {
+  acceptFactoryAuthority,
+  acceptStakingAuthority,
+  addRewardStream,
+  cancelUnstake,
   changeConfig,
+  changeFactoryFee,
+  changeStakingFee,
+  changeStreamConfig,
+  claimPenaltyShare,
   claimReward,
+  claimStreamReward,
+  closeMember,
+  closeStaking,
+  compound,
   createStaking,
   deposit,
+  emergencyUnstake,
+  depositAndStake,
   endUnstake,
+  endUnstakeAndWithdraw,
+  expireLock,
+  extendRewardStream,
+  extendStaking,
+  fundRewardStream,
   initialize,
+  instantUnstake,
+  lock,
+  proposeFactoryAuthority,
+  proposeStakingAuthority,
   registerMember,
+  setAutoCompound,
+  setEmergencyUnstake,
+  setFactoryPaused,
+  setStakingPaused,
   stake,
   startUnstake,
+  sunsetStaking,
   withdraw,
+  withdrawExcessRewards,
+  withdrawExcessStreamRewards,
 } from "./api";
 
 chai.use(chaiAsPromised);
@@ -27,11 +60,64 @@ before(async () => {
 
 describe("instructions", () => {
   it("initialize", async () => {
-    await initialize(ctx);
+    await initialize(ctx, 300);
 
     const factory = await ctx.program.account.factory.fetch(ctx.factory);
     expect(factory.bump).to.be.above(200);
     expect(factory.authority).to.eql(ctx.factoryAuthority.publicKey);
+    expect(factory.fee).to.eql(300);
+  });
+
+  it("changeFactoryFee", async () => {
+    await expect(changeFactoryFee(ctx, 1001)).to.be.rejectedWith(
+      "FeeTooHigh"
+    );
+
+    await changeFactoryFee(ctx, 500);
+    expect(
+      (await ctx.program.account.factory.fetch(ctx.factory)).fee
+    ).to.eql(500);
+
+    await changeFactoryFee(ctx, 300);
+  });
+
+  it("transfers factory authority", async () => {
+    const newAuthority = new Keypair();
+
+    await proposeFactoryAuthority(
+      ctx,
+      ctx.factoryAuthority,
+      newAuthority.publicKey
+    );
+    await expect(acceptFactoryAuthority(ctx, ctx.user1)).to.be.rejectedWith(
+      "NotPendingAuthority"
+    );
+
+    await proposeFactoryAuthority(ctx, ctx.factoryAuthority, null);
+    await expect(
+      acceptFactoryAuthority(ctx, newAuthority)
+    ).to.be.rejectedWith("NotPendingAuthority");
+
+    await proposeFactoryAuthority(
+      ctx,
+      ctx.factoryAuthority,
+      newAuthority.publicKey
+    );
+    await acceptFactoryAuthority(ctx, newAuthority);
+
+    let factory = await ctx.program.account.factory.fetch(ctx.factory);
+    expect(factory.authority).to.eql(newAuthority.publicKey);
+    expect(factory.pendingAuthority).to.eql(null);
+
+    await proposeFactoryAuthority(
+      ctx,
+      newAuthority,
+      ctx.factoryAuthority.publicKey
+    );
+    await acceptFactoryAuthority(ctx, ctx.factoryAuthority);
+
+    factory = await ctx.program.account.factory.fetch(ctx.factory);
+    expect(factory.authority).to.eql(ctx.factoryAuthority.publicKey);
   });
 
   it("createStaking", async () => {
@@ -56,12 +142,67 @@ describe("instructions", () => {
         },
       })
     ).to.be.rejectedWith("Zero");
+    await expect(
+      createStaking(ctx, unstakeTimelock, {
+        decayingProportional: {
+          initialAmount: new BN(100),
+          rewardPeriod: 10,
+          decayPeriods: 1,
+          decayNum: 3,
+          decayDenom: 2,
+        },
+      })
+    ).to.be.rejectedWith("InvalidDecay");
+    await expect(
+      createStaking(ctx, unstakeTimelock, {
+        decayingProportional: {
+          initialAmount: new BN(100),
+          rewardPeriod: 10,
+          decayPeriods: 1,
+          decayNum: 2,
+          decayDenom: 2,
+        },
+      })
+    ).to.be.rejectedWith("InvalidDecay");
+    const tier = (requiredAmount: number, rewardAmount: number) => ({
+      requiredAmount: new BN(requiredAmount),
+      rewardAmount: new BN(rewardAmount),
+    });
+    await expect(
+      createStaking(ctx, unstakeTimelock, {
+        tieredFixed: {
+          requiredPeriod: 10,
+          tiers: [tier(100, 10), tier(100, 20), tier(0, 0), tier(0, 0)],
+        },
+      })
+    ).to.be.rejectedWith("InvalidTiers");
 
     const rewardParams = {
       interestRate: { num: new BN(1337), denom: new BN(100) },
     };
+    await expect(
+      createStaking(
+        ctx,
+        unstakeTimelock,
+        rewardParams,
+        2_000_000_000,
+        1_000_000_000
+      )
+    ).to.be.rejectedWith("InvalidEndTs");
+    // an existing vault is accepted
+    await findATA(ctx, await ctx.staking(), ctx.rewardMint);
     await createStaking(ctx, unstakeTimelock, rewardParams);
 
+    expect(
+      await ctx.connection.getAccountInfo(
+        await getAssociatedTokenAddress(
+          ctx.stakeMint,
+          await ctx.staking(),
+          true
+        )
+      )
+    ).to.not.be.null;
+
     const staking = await ctx.program.account.staking.fetch(
       await ctx.staking()
     );
@@ -70,6 +211,8 @@ describe("instructions", () => {
     expect(staking.id).to.eql(0);
     expect(staking.stakeMint).to.eql(ctx.stakeMint);
     expect(staking.rewardMint).to.eql(ctx.rewardMint);
+    expect(staking.stakeDecimals).to.eql(2);
+    expect(staking.rewardDecimals).to.eql(6);
     expect(staking.unstakeTimelock).to.eql(unstakeTimelock);
     // @ts-ignore
     expect(staking.rewardParams.interestRate.num.toNumber()).to.eql(
@@ -84,32 +227,91 @@ describe("instructions", () => {
       await ctx.configHistory()
     );
     expect(configHistory.bump).to.be.above(200);
-    expect(configHistory.len).to.eql(1);
-    expect(configHistory.rewardParams[0].interestRate.num.toNumber()).to.eql(
+    expect(configHistory.pagesCount).to.eql(1);
+    expect(configHistory.settingsPagesCount).to.eql(1);
+    expect(configHistory.startTs).to.not.eql(0);
+    expect(configHistory.endTs).to.eql(4294967295);
+
+    const page = await ctx.program.account.configHistoryPage.fetch(
+      await ctx.configHistoryPage(await ctx.configHistory(), 0)
+    );
+    expect(page.rewardParams.length).to.eql(1);
+    expect(page.startTimestamps.length).to.eql(1);
+    expect(page.rewardParams[0].interestRate.num.toNumber()).to.eql(
       rewardParams.interestRate.num.toNumber()
     );
-    expect(configHistory.rewardParams[0].interestRate.denom.toNumber()).to.eql(
+    expect(page.rewardParams[0].interestRate.denom.toNumber()).to.eql(
       rewardParams.interestRate.denom.toNumber()
     );
-    expect(configHistory.startTimestamps[0]).to.not.eql(0);
+    expect(page.startTimestamps[0]).to.eql(configHistory.startTs);
 
-    const stakesHistory = await ctx.program.account.stakesHistory.fetch(
-      await ctx.stakesHistory()
-    );
-    expect(stakesHistory.bump).to.be.above(200);
+    expect(staking.rewardPerShare.toNumber()).to.eql(0);
+    expect(staking.rewardPerShareTs).to.eql(configHistory.startTs);
+
+    expect(staking.totalFunded.toNumber()).to.eql(1_000_000);
+    expect(staking.totalClaimed.toNumber()).to.eql(0);
+    expect(staking.rewardsLiability.toNumber()).to.eql(0);
+    expect(staking.instantUnstake).to.eql(null);
 
     const factory = await ctx.program.account.factory.fetch(ctx.factory);
     expect(factory.stakingsCount).to.eql(1);
   });
 
+  it("changeStakingFee", async () => {
+    await expect(changeStakingFee(ctx, 1001)).to.be.rejectedWith(
+      "FeeTooHigh"
+    );
+
+    await changeStakingFee(ctx, 100);
+    expect(
+      (await ctx.program.account.staking.fetch(await ctx.staking()))
+        .feeOverride
+    ).to.eql(100);
+
+    await changeStakingFee(ctx, null);
+    expect(
+      (await ctx.program.account.staking.fetch(await ctx.staking()))
+        .feeOverride
+    ).to.eql(null);
+  });
+
+  it("transfers staking authority", async () => {
+    const newAuthority = new Keypair();
+
+    await proposeStakingAuthority(
+      ctx,
+      ctx.stakingAuthority,
+      newAuthority.publicKey
+    );
+    await expect(acceptStakingAuthority(ctx, ctx.user1)).to.be.rejectedWith(
+      "NotPendingAuthority"
+    );
+    await acceptStakingAuthority(ctx, newAuthority);
+
+    let staking = await ctx.program.account.staking.fetch(await ctx.staking());
+    expect(staking.authority).to.eql(newAuthority.publicKey);
+    expect(staking.pendingAuthority).to.eql(null);
+
+    await proposeStakingAuthority(
+      ctx,
+      newAuthority,
+      ctx.stakingAuthority.publicKey
+    );
+    await acceptStakingAuthority(ctx, ctx.stakingAuthority);
+
+    staking = await ctx.program.account.staking.fetch(await ctx.staking());
+    expect(staking.authority).to.eql(ctx.stakingAuthority.publicKey);
+  });
+
+  it("extendStaking", async () => {
+    await expect(extendStaking(ctx, 2_000_000_000)).to.be.rejectedWith(
+      "InvalidEndTs"
+    );
+  });
+
   it("changeConfig", async () => {
     await changeConfig(ctx, null);
 
-    await expect(
-      changeConfig(ctx, {
-        proportional: { totalAmount: new BN(1), rewardPeriod: new BN(1) },
-      })
-    ).to.be.rejectedWith("CannotChangeStakingType");
     await expect(
       changeConfig(ctx, {
         fixed: {
@@ -118,7 +320,7 @@ describe("instructions", () => {
           rewardAmount: new BN(1),
         },
       })
-    ).to.be.rejectedWith("CannotChangeStakingType");
+    ).to.be.rejectedWith("Zero");
 
     await expect(
       changeConfig(ctx, {
@@ -126,6 +328,14 @@ describe("instructions", () => {
       })
     ).to.be.rejectedWith("Zero");
 
+    await changeConfig(ctx, {
+      fixed: {
+        requiredAmount: new BN(1),
+        requiredPeriod: new BN(1),
+        rewardAmount: new BN(1),
+      },
+    });
+
     const rewardParams = {
       interestRate: {
         num: new BN(10),
@@ -146,17 +356,64 @@ describe("instructions", () => {
       rewardParams.interestRate.denom.toNumber()
     );
 
-    const configHistory = await ctx.program.account.configHistory.fetch(
-      await ctx.configHistory()
+    const configHistory = await ctx.configHistory();
+    const page = await ctx.program.account.configHistoryPage.fetch(
+      await ctx.configHistoryPage(configHistory, 0)
     );
-    expect(configHistory.len).to.eql(2);
-    expect(configHistory.rewardParams[1].interestRate.num.toNumber()).to.eql(
+    expect(page.rewardParams.length).to.eql(3);
+    expect(page.startTimestamps.length).to.eql(3);
+    expect(page.rewardParams[1].fixed.requiredPeriod).to.eql(1);
+    expect(page.rewardParams[2].interestRate.num.toNumber()).to.eql(
       rewardParams.interestRate.num.toNumber()
     );
-    expect(configHistory.rewardParams[1].interestRate.denom.toNumber()).to.eql(
+    expect(page.rewardParams[2].interestRate.denom.toNumber()).to.eql(
       rewardParams.interestRate.denom.toNumber()
     );
-    expect(configHistory.startTimestamps[1]).to.not.eql(0);
+    expect(page.startTimestamps[2]).to.not.eql(0);
+
+    for (let i = 0; i < 32; i++) {
+      await changeConfig(ctx, rewardParams);
+    }
+
+    // pages hold 16 configs each
+    const grownConfigHistory = await ctx.program.account.configHistory.fetch(
+      configHistory
+    );
+    expect(grownConfigHistory.pagesCount).to.eql(3);
+    expect(grownConfigHistory.settingsPagesCount).to.eql(1);
+    const lastPage = await ctx.program.account.configHistoryPage.fetch(
+      await ctx.configHistoryPage(configHistory, 2)
+    );
+    expect(lastPage.rewardParams.length).to.eql(3);
+    expect(lastPage.startTimestamps.length).to.eql(3);
+
+    await expect(
+      changeConfig(ctx, null, {
+        unstakeTimelock: 10,
+        instantUnstake: { penalty: 10_001, policy: { burn: {} } },
+        lock: null,
+      })
+    ).to.be.rejectedWith("InvalidPenalty");
+    await expect(
+      changeConfig(ctx, null, {
+        unstakeTimelock: 10,
+        instantUnstake: null,
+        lock: { maxDuration: 100, maxMultiplier: 9_999 },
+      })
+    ).to.be.rejectedWith("InvalidLockParams");
+
+    await changeConfig(ctx, null, {
+      unstakeTimelock: 10,
+      instantUnstake: null,
+      lock: null,
+    });
+
+    const settingsPage = await ctx.program.account.settingsHistoryPage.fetch(
+      await ctx.settingsHistoryPage(configHistory, 0)
+    );
+    expect(settingsPage.settings.length).to.eql(2);
+    expect(settingsPage.settings[1].unstakeTimelock).to.eql(10);
+    expect(settingsPage.timestamps[1]).to.not.eql(0);
   });
 
   it("registerMember", async () => {
@@ -166,6 +423,7 @@ describe("instructions", () => {
       await ctx.member(ctx.user1.publicKey)
     );
     expect(member.bump).to.be.above(200);
+    expect(member.lockMultiplier).to.eql(10_000);
   });
 
   it("deposit", async () => {
@@ -200,6 +458,28 @@ describe("instructions", () => {
       await ctx.staking()
     );
     expect(staking.stakesSum.toNumber()).to.eql(100);
+    expect(staking.weightedStakesSum.toNumber()).to.eql(100);
+  });
+
+  it("lock", async () => {
+    await expect(lock(ctx, ctx.user1, 1)).to.be.rejectedWith("LockDisabled");
+  });
+
+  it("compound", async () => {
+    await expect(
+      compound(ctx, ctx.user1, ctx.user1.publicKey)
+    ).to.be.rejectedWith("MintsDiffer");
+  });
+
+  it("setAutoCompound", async () => {
+    await setAutoCompound(ctx, ctx.user1, true);
+
+    const member = await ctx.program.account.member.fetch(
+      await ctx.member(ctx.user1.publicKey)
+    );
+    expect(member.autoCompound).to.eql(true);
+
+    await setAutoCompound(ctx, ctx.user1, false);
   });
 
   it("claimReward", async () => {
@@ -216,35 +496,82 @@ describe("instructions", () => {
       await ctx.member(ctx.user1.publicKey)
     );
     expect(member.rewardsAmount.toNumber()).to.eql(0);
+
+    const staking = await ctx.program.account.staking.fetch(
+      await ctx.staking()
+    );
+    expect(staking.totalClaimed.toNumber()).to.be.oneOf([40, 50, 60]);
+    expect(staking.rewardsLiability.toNumber()).to.eql(0);
+  });
+
+  it("withdrawExcessRewards", async () => {
+    await expect(withdrawExcessRewards(ctx, 1_000_000)).to.be.rejectedWith(
+      "ReservedRewards"
+    );
+
+    await withdrawExcessRewards(ctx, 900_000);
+
+    const staking = await ctx.program.account.staking.fetch(
+      await ctx.staking()
+    );
+    expect(staking.totalFunded.toNumber()).to.eql(100_000);
   });
 
   it("startUnstake", async () => {
     await expect(endUnstake(ctx, ctx.user1)).to.be.rejectedWith(
       "UnstakeInactive"
     );
+    await expect(cancelUnstake(ctx, ctx.user1)).to.be.rejectedWith(
+      "UnstakeInactive"
+    );
 
     await expect(startUnstake(ctx, ctx.user1, 101)).to.be.rejectedWith(
       "InsufficientBalance"
     );
+    await expect(startUnstake(ctx, ctx.user1, 0)).to.be.rejectedWith("Zero");
 
-    await startUnstake(ctx, ctx.user1, 100);
+    await startUnstake(ctx, ctx.user1, 60);
+    await startUnstake(ctx, ctx.user1, 40);
 
     const member = await ctx.program.account.member.fetch(
       await ctx.member(ctx.user1.publicKey)
     );
-    expect(member.pendingUnstakeActive).to.eql(true);
-    expect(member.pendingUnstakeEndTs).to.not.eql(0);
+    expect(member.pendingUnstakes[0].amount.toNumber()).to.eql(60);
+    expect(member.pendingUnstakes[0].endTs).to.not.eql(0);
+    expect(member.pendingUnstakes[1].amount.toNumber()).to.eql(40);
+    expect(member.pendingUnstakes[2].amount.toNumber()).to.eql(0);
     expect(member.stakeAmount.toNumber()).to.eql(0);
     expect(member.pendingAmount.toNumber()).to.eql(100);
 
     const staking = await ctx.program.account.staking.fetch(
       await ctx.staking()
     );
-    expect(staking.stakesSum.toNumber()).to.eql(100);
+    expect(staking.stakesSum.toNumber()).to.eql(0);
+  });
+
+  it("cancelUnstake", async () => {
+    await cancelUnstake(ctx, ctx.user1);
+
+    const member = await ctx.program.account.member.fetch(
+      await ctx.member(ctx.user1.publicKey)
+    );
+    expect(member.pendingUnstakes[0].amount.toNumber()).to.eql(0);
+    expect(member.pendingUnstakes[1].amount.toNumber()).to.eql(0);
+    expect(member.stakeAmount.toNumber()).to.eql(100);
+    expect(member.pendingAmount.toNumber()).to.eql(0);
 
-    await expect(startUnstake(ctx, ctx.user1, 0)).to.be.rejectedWith(
-      "UnstakeActive"
+    const staking = await ctx.program.account.staking.fetch(
+      await ctx.staking()
     );
+    expect(staking.stakesSum.toNumber()).to.eql(100);
+
+    await startUnstake(ctx, ctx.user1, 100);
+  });
+
+  it("instantUnstake", async () => {
+    await expect(
+      instantUnstake(ctx, ctx.user1, 1, ctx.stakeMint)
+    ).to.be.rejectedWith("InstantUnstakeDisabled");
   });
 
   it("endUnstake", async () => {
@@ -260,7 +587,26 @@ describe("instructions", () => {
     );
     expect(member.availableAmount.toNumber()).to.eql(100);
     expect(member.pendingAmount.toNumber()).to.eql(0);
-    expect(member.pendingUnstakeActive).to.eql(false);
+    expect(member.pendingUnstakes[0].amount.toNumber()).to.eql(0);
+  });
+
+  it("pause", async () => {
+    await expect(setFactoryPaused(ctx, 16)).to.be.rejectedWith(
+      "InvalidPauseFlags"
+    );
+    await expect(setStakingPaused(ctx, ctx.user1, 2)).to.be.rejectedWith(
+      "NotAuthority"
+    );
+
+    // stake
+    await setFactoryPaused(ctx, 2);
+    await expect(stake(ctx, ctx.user1, 1)).to.be.rejectedWith("Paused");
+    await setFactoryPaused(ctx, 0);
+
+    // all but withdraw
+    await setStakingPaused(ctx, ctx.factoryAuthority, 15);
+    await expect(claimReward(ctx, ctx.user1)).to.be.rejectedWith("Paused");
+    await setStakingPaused(ctx, ctx.factoryAuthority, 0);
   });
 
   it("withdraw", async () => {
@@ -280,6 +626,74 @@ describe("instructions", () => {
     expect(member.availableAmount.toNumber()).to.eql(0);
   });
 
+  it("depositAndStake", async () => {
+    await depositAndStake(ctx, ctx.user1, 50);
+
+    expect(await (await ctx.stakeATA(ctx.user1.publicKey)).amount(ctx)).to.eql(
+      100
+    );
+
+    const member = await ctx.program.account.member.fetch(
+      await ctx.member(ctx.user1.publicKey)
+    );
+    expect(member.availableAmount.toNumber()).to.eql(0);
+    expect(member.stakeAmount.toNumber()).to.eql(50);
+  });
+
+  it("endUnstakeAndWithdraw", async () => {
+    await expect(endUnstakeAndWithdraw(ctx, ctx.user1)).to.be.rejectedWith(
+      "UnstakeInactive"
+    );
+
+    await startUnstake(ctx, ctx.user1, 50);
+    await expect(endUnstakeAndWithdraw(ctx, ctx.user1)).to.be.rejectedWith(
+      "UnstakeTimelock"
+    );
+  });
+
+  it("emergencyUnstake", async () => {
+    await expect(emergencyUnstake(ctx, ctx.user1)).to.be.rejectedWith(
+      "EmergencyUnstakeDisabled"
+    );
+
+    await setEmergencyUnstake(ctx, ctx.factoryAuthority, true);
+    await emergencyUnstake(ctx, ctx.user1);
+    await setEmergencyUnstake(ctx, ctx.factoryAuthority, false);
+
+    const member = await ctx.program.account.member.fetch(
+      await ctx.member(ctx.user1.publicKey)
+    );
+    expect(member.availableAmount.toNumber()).to.eql(50);
+    expect(member.pendingAmount.toNumber()).to.eql(0);
+    expect(member.pendingUnstakes[0].amount.toNumber()).to.eql(0);
+  });
+
+  it("closeMember", async () => {
+    await expect(closeMember(ctx, ctx.user1)).to.be.rejectedWith(
+      "MemberNotEmpty"
+    );
+
+    await registerMember(ctx, ctx.user2);
+    // dust sent to the member vault directly goes to the beneficiary
+    await mintTo(
+      ctx,
+      await ctx.stakeATA(await ctx.member(ctx.user2.publicKey)),
+      ctx.mintAuthority,
+      7
+    );
+    const balance = await (await ctx.stakeATA(ctx.user2.publicKey)).amount(ctx);
+    await closeMember(ctx, ctx.user2);
+    expect(await (await ctx.stakeATA(ctx.user2.publicKey)).amount(ctx)).to.eql(
+      balance + 7
+    );
+
+    expect(
+      await ctx.program.account.member.fetchNullable(
+        await ctx.member(ctx.user2.publicKey)
+      )
+    ).to.be.null;
+  });
+
   after(async () => {
     await ctx.teardown();
   });
@@ -402,6 +816,32 @@ describe("interest rate", () => {
     expect(member.rewardsAmount.toNumber()).to.eql(0);
   });
 
+  it("closes", async () => {
+    await expect(closeStaking(ctx)).to.be.rejectedWith("StakingNotClosing");
+
+    await sunsetStaking(ctx);
+    await expect(stake(ctx, ctx.user1, 1)).to.be.rejectedWith(
+      "StakingClosing"
+    );
+    await deposit(ctx, ctx.user1, 1);
+    await expect(closeStaking(ctx)).to.be.rejectedWith("MembersRemaining");
+    await withdraw(ctx, ctx.user1, 1);
+    await claimReward(ctx, ctx.user1);
+
+    // the empty member is left open and does not block closing
+    await closeStaking(ctx);
+
+    expect(await ctx.connection.getAccountInfo(await ctx.staking())).to.be
+      .null;
+    expect(await ctx.connection.getAccountInfo(await ctx.configHistory())).to
+      .be.null;
+    expect(
+      await ctx.connection.getAccountInfo(
+        await ctx.configHistoryPage(await ctx.configHistory(), 0)
+      )
+    ).to.be.null;
+  });
+
   after(async () => {
     await ctx.teardown();
   });
@@ -846,6 +1286,145 @@ describe("proportional (2 users)", () => {
   });
 });
 
+describe("changing staking type", () => {
+  const fetchMember = async () =>
+    await ctx.program.account.member.fetch(
+      await ctx.member(ctx.user1.publicKey)
+    );
+  const totalClaimed = async () =>
+    (
+      await ctx.program.account.staking.fetch(await ctx.staking())
+    ).totalClaimed.toNumber();
+  const boundaryTs = async () =>
+    (
+      await ctx.program.account.configHistoryPage.fetch(
+        await ctx.configHistoryPage(await ctx.configHistory(), 0)
+      )
+    ).startTimestamps[1];
+
+  describe("proportional to interest rate", () => {
+    it("stakes", async () => {
+      await createStaking(ctx, 0, {
+        proportional: { totalAmount: new BN(100), rewardPeriod: 10 },
+      });
+      await registerMember(ctx, ctx.user1);
+      await deposit(ctx, ctx.user1, 100);
+      await stake(ctx, ctx.user1, 100);
+    });
+
+    it("pays both sides of the period boundary", async () => {
+      await changeConfig(ctx, {
+        interestRate: { num: new BN(1), denom: new BN(10) },
+      });
+      const configHistory = await ctx.program.account.configHistory.fetch(
+        await ctx.configHistory()
+      );
+      // starts once the reward period in progress is finished
+      expect(await boundaryTs()).to.eql(configHistory.startTs + 10);
+
+      await sleep(12000);
+      await claimReward(ctx, ctx.user1);
+
+      // the whole proportional period, then 10 per second
+      const { lastRewardTs } = await fetchMember();
+      expect(await totalClaimed()).to.eql(
+        100 + 10 * (lastRewardTs - (await boundaryTs()))
+      );
+    });
+
+    after(async () => {
+      await ctx.teardown();
+    });
+  });
+
+  describe("fixed to proportional", () => {
+    let stakeTs: number;
+
+    it("stakes", async () => {
+      await createStaking(ctx, 0, {
+        fixed: {
+          requiredAmount: new BN(100),
+          requiredPeriod: 10,
+          rewardAmount: new BN(100),
+        },
+      });
+      await registerMember(ctx, ctx.user1);
+      await deposit(ctx, ctx.user1, 100);
+      await stake(ctx, ctx.user1, 100);
+      stakeTs = (await fetchMember()).lastRewardTs;
+    });
+
+    it("pays the partial fixed period and then proportionally", async () => {
+      await sleep(3000);
+      await changeConfig(ctx, {
+        proportional: { totalAmount: new BN(100), rewardPeriod: 10 },
+      });
+
+      await sleep(11000);
+      await claimReward(ctx, ctx.user1);
+
+      // fixed until the change, then one whole proportional period
+      const boundary = await boundaryTs();
+      expect(boundary - stakeTs).to.be.within(1, 9);
+      expect(await totalClaimed()).to.eql(
+        Math.floor((100 * (boundary - stakeTs)) / 10) + 100
+      );
+    });
+
+    after(async () => {
+      await ctx.teardown();
+    });
+  });
+});
+
+describe("decaying proportional", () => {
+  const totalClaimed = async () =>
+    (
+      await ctx.program.account.staking.fetch(await ctx.staking())
+    ).totalClaimed.toNumber();
+
+  it("creates staking", async () => {
+    // halves every reward period, 2000 in total
+    await createStaking(
+      ctx,
+      0,
+      {
+        decayingProportional: {
+          initialAmount: new BN(1000),
+          rewardPeriod: 2,
+          decayPeriods: 1,
+          decayNum: 1,
+          decayDenom: 2,
+        },
+      },
+      Math.floor(Date.now() / 1000) + 4
+    );
+  });
+
+  it("stakes", async () => {
+    await registerMember(ctx, ctx.user1);
+    await deposit(ctx, ctx.user1, 100);
+    await stake(ctx, ctx.user1, 100);
+  });
+
+  it("tapers", async () => {
+    await sleep(14000);
+    await claimReward(ctx, ctx.user1);
+
+    // at least 1000 + 500 + 250 + 125
+    expect(await totalClaimed()).to.be.within(1875, 1999);
+
+    await sleep(6000);
+    await claimReward(ctx, ctx.user1);
+
+    expect(await totalClaimed()).to.be.within(1960, 1999);
+  });
+
+  after(async () => {
+    await ctx.teardown();
+  });
+});
+
 describe("fixed", () => {
   it("creates staking", async () => {
     await createStaking(ctx, 0, {
@@ -897,6 +1476,20 @@ describe("fixed", () => {
     expect(member.rewardsAmount.toNumber()).to.eql(0);
   });
 
+  it("reserves rewards of members not settled", async () => {
+    await sleep(3000);
+
+    await expect(withdrawExcessRewards(ctx, 1_000_000)).to.be.rejectedWith(
+      "ReservedRewards"
+    );
+    await withdrawExcessRewards(ctx, 1);
+
+    const staking = await ctx.program.account.staking.fetch(
+      await ctx.staking()
+    );
+    expect(staking.rewardsLiability.toNumber()).to.be.at.least(30);
+  });
+
   it("claims", async () => {
     await sleep(10000);
 
@@ -962,6 +1555,564 @@ describe("fixed", () => {
     expect(member.rewardsAmount.toNumber()).to.eql(0);
   });
 
+  it("releases reserved rewards on emergency unstake", async () => {
+    await depositAndStake(ctx, ctx.user1, 100);
+    await sleep(3000);
+
+    const { rewardsLiability } = await ctx.program.account.staking.fetch(
+      await ctx.staking()
+    );
+    await setEmergencyUnstake(ctx, ctx.factoryAuthority, true);
+    await emergencyUnstake(ctx, ctx.user1);
+    await setEmergencyUnstake(ctx, ctx.factoryAuthority, false);
+
+    const staking = await ctx.program.account.staking.fetch(
+      await ctx.staking()
+    );
+    expect(staking.rewardsLiability.toNumber()).to.be.below(
+      rewardsLiability.toNumber()
+    );
+    const member = await ctx.program.account.member.fetch(
+      await ctx.member(ctx.user1.publicKey)
+    );
+    expect(member.availableAmount.toNumber()).to.eql(100);
+    expect(member.fixedReserve.toNumber()).to.eql(0);
+  });
+
+  after(async () => {
+    await ctx.teardown();
+  });
+});
+
+describe("tiered fixed", () => {
+  const tier = (requiredAmount: number, rewardAmount: number) => ({
+    requiredAmount: new BN(requiredAmount),
+    rewardAmount: new BN(rewardAmount),
+  });
+
+  it("creates staking", async () => {
+    await createStaking(ctx, 0, {
+      tieredFixed: {
+        requiredPeriod: 10,
+        tiers: [tier(100, 10), tier(200, 30), tier(0, 0), tier(0, 0)],
+      },
+    });
+  });
+
+  it("stakes", async () => {
+    await registerMember(ctx, ctx.user1);
+    await deposit(ctx, ctx.user1, 100);
+    await stake(ctx, ctx.user1, 50);
+
+    await registerMember(ctx, ctx.user2);
+    await deposit(ctx, ctx.user2, 200);
+    await stake(ctx, ctx.user2, 200);
+  });
+
+  it("pays by tier", async () => {
+    await sleep(11000);
+
+    await claimReward(ctx, ctx.user1);
+    await claimReward(ctx, ctx.user2);
+
+    // below the first tier
+    expect(await (await ctx.rewardATA(ctx.user1.publicKey)).amount(ctx)).to.eql(
+      0
+    );
+    expect(await (await ctx.rewardATA(ctx.user2.publicKey)).amount(ctx)).to.eql(
+      30
+    );
+  });
+
+  it("pays the reached tier from then on", async () => {
+    await stake(ctx, ctx.user1, 50);
+    await sleep(11000);
+
+    await claimReward(ctx, ctx.user1);
+
+    expect(await (await ctx.rewardATA(ctx.user1.publicKey)).amount(ctx)).to.eql(
+      10
+    );
+  });
+
+  after(async () => {
+    await ctx.teardown();
+  });
+});
+
+describe("compound", () => {
+  let rewardMint: PublicKey;
+  let factoryVault: TokenAccount;
+
+  const fetchMember = async () =>
+    await ctx.program.account.member.fetch(
+      await ctx.member(ctx.user1.publicKey)
+    );
+  const fetchStaking = async () =>
+    await ctx.program.account.staking.fetch(await ctx.staking());
+
+  before(async () => {
+    // rewards are paid in the stake token
+    rewardMint = ctx.rewardMint;
+    factoryVault = ctx.factoryVault;
+    ctx.rewardMint = ctx.stakeMint;
+    ctx.factoryVault = await ctx.rewardATA(ctx.factoryAuthority.publicKey);
+  });
+
+  it("creates staking", async () => {
+    await createStaking(ctx, 0, {
+      interestRate: { num: new BN(1), denom: new BN(1) },
+    });
+  });
+
+  it("stakes", async () => {
+    await registerMember(ctx, ctx.user1);
+    await deposit(ctx, ctx.user1, 100);
+    await stake(ctx, ctx.user1, 100);
+  });
+
+  it("compounds", async () => {
+    await sleep(3000);
+    await compound(ctx, ctx.user1, ctx.user1.publicKey);
+
+    const member = await fetchMember();
+    const staking = await fetchStaking();
+    const compounded = member.stakeAmount.toNumber() - 100;
+    const factoryFee = await ctx.factoryVault.amount(ctx);
+    expect(compounded).to.be.above(0);
+    expect(staking.totalClaimed.toNumber()).to.eql(compounded + factoryFee);
+    expect(factoryFee).to.eql(
+      Math.floor((staking.totalClaimed.toNumber() * 300) / 10_000)
+    );
+    expect(factoryFee).to.be.above(0);
+    expect(member.rewardsAmount.toNumber()).to.eql(0);
+    expect(staking.stakesSum.toNumber()).to.eql(member.stakeAmount.toNumber());
+    expect(
+      await (
+        await ctx.stakeATA(await ctx.member(ctx.user1.publicKey))
+      ).amount(ctx)
+    ).to.eql(member.stakeAmount.toNumber());
+  });
+
+  it("compounds by a third party", async () => {
+    await expect(
+      compound(ctx, ctx.user2, ctx.user1.publicKey)
+    ).to.be.rejectedWith("AutoCompoundDisabled");
+
+    await setAutoCompound(ctx, ctx.user1, true);
+    const stakeAmount = (await fetchMember()).stakeAmount.toNumber();
+    await sleep(2000);
+    await compound(ctx, ctx.user2, ctx.user1.publicKey);
+
+    const member = await fetchMember();
+    expect(member.stakeAmount.toNumber()).to.be.above(stakeAmount);
+    expect((await fetchStaking()).stakesSum.toNumber()).to.eql(
+      member.stakeAmount.toNumber()
+    );
+  });
+
+  after(async () => {
+    await ctx.teardown();
+    ctx.rewardMint = rewardMint;
+    ctx.factoryVault = factoryVault;
+  });
+});
+
+describe("lock", () => {
+  const fetchMember = async () =>
+    await ctx.program.account.member.fetch(
+      await ctx.member(ctx.user1.publicKey)
+    );
+
+  it("creates staking", async () => {
+    await createStaking(
+      ctx,
+      0,
+      { interestRate: { num: new BN(0), denom: new BN(1) } },
+      null,
+      null,
+      null,
+      { maxDuration: 10, maxMultiplier: 20_000 }
+    );
+  });
+
+  it("stakes", async () => {
+    await registerMember(ctx, ctx.user1);
+    await deposit(ctx, ctx.user1, 200);
+    await stake(ctx, ctx.user1, 100);
+  });
+
+  it("boosts the weight", async () => {
+    await lock(ctx, ctx.user1, 10);
+
+    const member = await fetchMember();
+    expect(member.lockMultiplier).to.eql(20_000);
+    expect(member.weightedAmount.toNumber()).to.eql(200);
+    const staking = await ctx.program.account.staking.fetch(
+      await ctx.staking()
+    );
+    expect(staking.weightedStakesSum.toNumber()).to.eql(200);
+
+    await expect(startUnstake(ctx, ctx.user1, 100)).to.be.rejectedWith(
+      "StakeLocked"
+    );
+  });
+
+  it("boosts added stake by the remaining lock", async () => {
+    await sleep(2000);
+    await stake(ctx, ctx.user1, 100);
+
+    const member = await fetchMember();
+    expect(member.stakeAmount.toNumber()).to.eql(200);
+    expect(member.weightedAmount.toNumber()).to.be.above(300);
+    expect(member.weightedAmount.toNumber()).to.be.below(400);
+    expect(member.lockMultiplier).to.be.above(15_000);
+    expect(member.lockMultiplier).to.be.below(20_000);
+  });
+
+  it("expires", async () => {
+    await expect(expireLock(ctx, ctx.user1.publicKey)).to.be.rejectedWith(
+      "StakeLocked"
+    );
+
+    await sleep(10000);
+    await setStakingPaused(ctx, ctx.stakingAuthority, 2);
+    await expect(expireLock(ctx, ctx.user1.publicKey)).to.be.rejectedWith(
+      "Paused"
+    );
+    await setStakingPaused(ctx, ctx.stakingAuthority, 0);
+    await expireLock(ctx, ctx.user1.publicKey);
+
+    const member = await fetchMember();
+    expect(member.lockMultiplier).to.eql(10_000);
+    expect(member.weightedAmount.toNumber()).to.eql(200);
+    const staking = await ctx.program.account.staking.fetch(
+      await ctx.staking()
+    );
+    expect(staking.weightedStakesSum.toNumber()).to.eql(200);
+
+    await startUnstake(ctx, ctx.user1, 200);
+  });
+
+  after(async () => {
+    await ctx.teardown();
+  });
+});
+
+describe("instant unstake", () => {
+  const settings = (policy: any) => ({
+    unstakeTimelock: 100,
+    instantUnstake: { penalty: 1_000, policy },
+    lock: null,
+  });
+
+  it("creates staking", async () => {
+    await createStaking(
+      ctx,
+      100,
+      { interestRate: { num: new BN(0), denom: new BN(1) } },
+      null,
+      null,
+      { penalty: 1_000, policy: { burn: {} } }
+    );
+  });
+
+  it("stakes", async () => {
+    for (const user of [ctx.user1, ctx.user2]) {
+      await registerMember(ctx, user);
+      await deposit(ctx, user, 100);
+      await stake(ctx, user, 100);
+    }
+  });
+
+  it("burns the penalty", async () => {
+    await expect(
+      instantUnstake(
+        ctx,
+        ctx.user1,
+        100,
+        await ctx.stakeATA(ctx.factoryAuthority.publicKey)
+      )
+    ).to.be.rejectedWith("InvalidPenaltyDestination");
+
+    await instantUnstake(ctx, ctx.user1, 100, ctx.stakeMint);
+
+    const member = await ctx.program.account.member.fetch(
+      await ctx.member(ctx.user1.publicKey)
+    );
+    expect(member.stakeAmount.toNumber()).to.eql(0);
+    expect(member.availableAmount.toNumber()).to.eql(90);
+    expect(
+      await (
+        await ctx.stakeATA(await ctx.member(ctx.user1.publicKey))
+      ).amount(ctx)
+    ).to.eql(90);
+  });
+
+  it("sends the penalty to factory", async () => {
+    await changeConfig(ctx, null, settings({ factory: {} }));
+
+    const factoryVault = await ctx.stakeATA(ctx.factoryAuthority.publicKey);
+    const factoryAmount = await factoryVault.amount(ctx);
+
+    await expect(
+      instantUnstake(ctx, ctx.user2, 50, ctx.stakeMint)
+    ).to.be.rejectedWith("InvalidPenaltyDestination");
+    await instantUnstake(ctx, ctx.user2, 50, factoryVault);
+
+    expect(await factoryVault.amount(ctx)).to.eql(factoryAmount + 5);
+    const member = await ctx.program.account.member.fetch(
+      await ctx.member(ctx.user2.publicKey)
+    );
+    expect(member.stakeAmount.toNumber()).to.eql(50);
+    expect(member.availableAmount.toNumber()).to.eql(45);
+  });
+
+  it("redistributes the penalty", async () => {
+    await changeConfig(ctx, null, settings({ redistribute: {} }));
+    await stake(ctx, ctx.user1, 90);
+
+    const stakingStakeVault = await ctx.stakeATA(await ctx.staking());
+    await instantUnstake(ctx, ctx.user2, 50, stakingStakeVault);
+
+    expect(await stakingStakeVault.amount(ctx)).to.eql(5);
+    const staking = await ctx.program.account.staking.fetch(
+      await ctx.staking()
+    );
+    expect(staking.stakesSum.toNumber()).to.eql(90);
+    expect(staking.penaltyPerShare.toString()).to.not.eql("0");
+
+    const member2 = await ctx.program.account.member.fetch(
+      await ctx.member(ctx.user2.publicKey)
+    );
+    expect(member2.stakeAmount.toNumber()).to.eql(0);
+    expect(member2.availableAmount.toNumber()).to.eql(90);
+  });
+
+  it("claims penalty share", async () => {
+    await expect(claimPenaltyShare(ctx, ctx.user2)).to.be.rejectedWith("Zero");
+
+    await claimPenaltyShare(ctx, ctx.user1);
+
+    expect(await (await ctx.stakeATA(await ctx.staking())).amount(ctx)).to.eql(
+      0
+    );
+    const member = await ctx.program.account.member.fetch(
+      await ctx.member(ctx.user1.publicKey)
+    );
+    expect(member.stakeAmount.toNumber()).to.eql(90);
+    expect(member.availableAmount.toNumber()).to.eql(5);
+    expect(member.penaltyShareAmount.toNumber()).to.eql(0);
+  });
+
+  after(async () => {
+    await ctx.teardown();
+  });
+});
+
+describe("ended staking", () => {
+  it("creates staking", async () => {
+    const ts = await ctx.connection.getBlockTime(
+      await ctx.connection.getSlot()
+    );
+    await createStaking(
+      ctx,
+      0,
+      { proportional: { totalAmount: new BN(1000), rewardPeriod: 10 } },
+      null,
+      ts + 6
+    );
+  });
+
+  it("stakes", async () => {
+    await registerMember(ctx, ctx.user1);
+    await deposit(ctx, ctx.user1, 100);
+    await stake(ctx, ctx.user1, 100);
+  });
+
+  it("does not accrue after the end", async () => {
+    // starts once the reward period in progress is finished, after the end
+    await changeConfig(ctx, {
+      interestRate: { num: new BN(1), denom: new BN(1) },
+    });
+
+    await sleep(12000);
+
+    await expect(
+      changeConfig(ctx, {
+        interestRate: { num: new BN(1), denom: new BN(1) },
+      })
+    ).to.be.rejectedWith("StakingEnded");
+
+    await claimReward(ctx, ctx.user1);
+
+    expect(await (await ctx.rewardATA(ctx.user1.publicKey)).amount(ctx)).to.eql(
+      0
+    );
+    const staking = await ctx.program.account.staking.fetch(
+      await ctx.staking()
+    );
+    expect(staking.rewardsLiability.toNumber()).to.eql(0);
+  });
+
+  after(async () => {
+    await ctx.teardown();
+  });
+});
+
+describe("reward streams", () => {
+  let partnerMint: PublicKey;
+  const endTs = Math.floor(Date.now() / 1000) + 3600;
+
+  it("creates staking", async () => {
+    await createStaking(ctx, 0, {
+      interestRate: { num: new BN(10), denom: new BN(100) },
+    });
+  });
+
+  it("adds reward stream", async () => {
+    partnerMint = await createMint(ctx, ctx.mintAuthority, 6);
+    // an existing vault is accepted
+    await findATA(ctx, await ctx.staking(), partnerMint);
+
+    await addRewardStream(
+      ctx,
+      partnerMint,
+      { interestRate: { num: new BN(20), denom: new BN(100) } },
+      null,
+      endTs
+    );
+    await fundRewardStream(ctx, 0, partnerMint, 1_000_000);
+
+    const staking = await ctx.program.account.staking.fetch(
+      await ctx.staking()
+    );
+    expect(staking.rewardStreamsCount).to.eql(1);
+
+    const rewardStream = await ctx.program.account.rewardStream.fetch(
+      await ctx.rewardStream(0)
+    );
+    expect(rewardStream.index).to.eql(0);
+    expect(rewardStream.rewardMint).to.eql(partnerMint);
+    expect(rewardStream.totalFunded.toNumber()).to.eql(1_000_000);
+  });
+
+  it("rejects the staking mints", async () => {
+    for (const mint of [ctx.rewardMint, ctx.stakeMint]) {
+      await expect(
+        addRewardStream(ctx, mint, {
+          interestRate: { num: new BN(20), denom: new BN(100) },
+        })
+      ).to.be.rejectedWith("InvalidRewardStreams");
+    }
+  });
+
+  it("registers", async () => {
+    await registerMember(ctx, ctx.user1);
+  });
+
+  it("deposits", async () => {
+    await deposit(ctx, ctx.user1, 100);
+  });
+
+  it("stakes", async () => {
+    await stake(ctx, ctx.user1, 100);
+  });
+
+  it("claims", async () => {
+    await sleep(4000);
+
+    await claimReward(ctx, ctx.user1);
+
+    // settled, but claimed separately
+    let member = await ctx.program.account.member.fetch(
+      await ctx.member(ctx.user1.publicKey)
+    );
+    expect(member.rewardsAmount.toNumber()).to.eql(0);
+    expect(member.rewardStreams[0].rewardsAmount.toNumber()).to.be.above(0);
+    expect(
+      await (await findATA(ctx, ctx.user1.publicKey, partnerMint)).amount(ctx)
+    ).to.eql(0);
+
+    await claimStreamReward(ctx, ctx.user1, 0);
+
+    expect(
+      await (await findATA(ctx, ctx.user1.publicKey, partnerMint)).amount(ctx)
+    ).to.be.above(0);
+
+    member = await ctx.program.account.member.fetch(
+      await ctx.member(ctx.user1.publicKey)
+    );
+    expect(member.rewardStreams[0].rewardsAmount.toNumber()).to.eql(0);
+
+    const rewardStream = await ctx.program.account.rewardStream.fetch(
+      await ctx.rewardStream(0)
+    );
+    expect(rewardStream.totalClaimed.toNumber()).to.be.above(0);
+  });
+
+  it("changes config", async () => {
+    await changeStreamConfig(ctx, 0, {
+      interestRate: { num: new BN(30), denom: new BN(100) },
+    });
+
+    const rewardStream = await ctx.program.account.rewardStream.fetch(
+      await ctx.rewardStream(0)
+    );
+    expect(rewardStream.rewardParams.interestRate.num.toNumber()).to.eql(30);
+    const page = await ctx.program.account.configHistoryPage.fetch(
+      await ctx.configHistoryPage(await ctx.rewardStreamConfigHistory(0), 0)
+    );
+    expect(page.rewardParams.length).to.eql(2);
+  });
+
+  it("extends", async () => {
+    const configHistory = await ctx.rewardStreamConfigHistory(0);
+    await extendRewardStream(ctx, 0, endTs + 3600);
+    expect(
+      (await ctx.program.account.configHistory.fetch(configHistory)).endTs
+    ).to.eql(endTs + 3600);
+  });
+
+  it("withdraws excess rewards", async () => {
+    await expect(
+      withdrawExcessStreamRewards(ctx, 0, partnerMint, 1_000_000)
+    ).to.be.rejectedWith("ReservedRewards");
+
+    await withdrawExcessStreamRewards(ctx, 0, partnerMint, 100_000);
+
+    const rewardStream = await ctx.program.account.rewardStream.fetch(
+      await ctx.rewardStream(0)
+    );
+    expect(rewardStream.totalFunded.toNumber()).to.eql(900_000);
+  });
+
+  it("starts unstake", async () => {
+    await startUnstake(ctx, ctx.user1, 100);
+
+    const member = await ctx.program.account.member.fetch(
+      await ctx.member(ctx.user1.publicKey)
+    );
+    expect(member.stakeAmount.toNumber()).to.eql(0);
+    expect(member.rewardStreams[0].lastRewardTs).to.not.eql(0);
+  });
+
+  it("sunsets", async () => {
+    const endTs = Math.floor(Date.now() / 1000) + 60;
+    await sunsetStaking(ctx, endTs);
+
+    expect(
+      (
+        await ctx.program.account.configHistory.fetch(
+          await ctx.rewardStreamConfigHistory(0)
+        )
+      ).endTs
+    ).to.eql(endTs);
+    await expect(cancelUnstake(ctx, ctx.user1)).to.be.rejectedWith(
+      "StakingClosing"
+    );
+  });
+
   after(async () => {
     await ctx.teardown();
   });